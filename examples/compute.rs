@@ -0,0 +1,151 @@
+use std::borrow::Cow;
+
+use glass::{
+    device_context::{DeviceConfig, DeviceContext},
+    ComputeApp, ComputeRunner, GlassError,
+};
+use image::GrayImage;
+use wgpu::{
+    util::DeviceExt, BindGroup, Buffer, BufferUsages, CommandEncoder, ComputePipeline,
+    ComputePipelineDescriptor, ShaderModuleDescriptor,
+};
+
+const WIDTH: u32 = 256;
+const NUM_STEPS: usize = 127;
+
+/// Rule 30 cellular automaton. Each step computes the next row of cells on the gpu, which is read
+/// back and written to an image at the end.
+fn main() -> Result<(), GlassError> {
+    let mut runner = ComputeRunner::new(Rule30App::default(), DeviceConfig::default())?;
+    runner.run_steps(NUM_STEPS)?;
+    runner.end();
+    let rows = &runner.app().rows;
+    let image = GrayImage::from_fn(WIDTH, rows.len() as u32, |x, y| {
+        image::Luma([if rows[y as usize][x as usize] == 1 {
+            0
+        } else {
+            255
+        }])
+    });
+    image
+        .save("compute_rule30.png")
+        .map_err(|e| GlassError::ImageError {
+            name: "compute_rule30.png".to_owned(),
+            source: e,
+        })?;
+    println!(
+        "Ran {} steps and saved compute_rule30.png",
+        runner.step_count()
+    );
+    Ok(())
+}
+
+#[derive(Default)]
+struct Rule30App {
+    data: Option<Rule30Data>,
+    rows: Vec<Vec<u32>>,
+}
+
+struct Rule30Data {
+    pipeline: ComputePipeline,
+    cells: [Buffer; 2],
+    // Bind group i reads cells[i] and writes cells[1 - i]
+    bind_groups: [BindGroup; 2],
+    current: usize,
+}
+
+impl ComputeApp for Rule30App {
+    fn start(&mut self, context: &DeviceContext) -> Result<(), GlassError> {
+        let mut initial = vec![0u32; WIDTH as usize];
+        initial[WIDTH as usize / 2] = 1;
+        self.rows.push(initial.clone());
+        self.data = Some(create_rule30_data(context, &initial));
+        Ok(())
+    }
+
+    fn step(
+        &mut self,
+        _context: &DeviceContext,
+        encoder: &mut CommandEncoder,
+    ) -> Result<(), GlassError> {
+        let data = self.data.as_mut().unwrap();
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Rule 30"),
+            });
+            cpass.set_pipeline(&data.pipeline);
+            cpass.set_bind_group(0, &data.bind_groups[data.current], &[]);
+            cpass.dispatch_workgroups(WIDTH / 64, 1, 1);
+        }
+        data.current = 1 - data.current;
+        Ok(())
+    }
+
+    fn after_step(&mut self, context: &DeviceContext) -> Result<(), GlassError> {
+        let data = self.data.as_ref().unwrap();
+        let row = context.read_buffer_as::<u32>(&data.cells[data.current])?;
+        self.rows.push(row);
+        Ok(())
+    }
+}
+
+fn create_rule30_data(context: &DeviceContext, initial: &[u32]) -> Rule30Data {
+    let device = context.device();
+    let cells = [0, 1].map(|_| {
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Cells"),
+            contents: bytemuck::cast_slice(initial),
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+        })
+    });
+    let shader = device.create_shader_module(ShaderModuleDescriptor {
+        label: Some("Rule 30 Shader"),
+        source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(RULE30_SHADER)),
+    });
+    let pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+        label: Some("Rule 30 Pipeline"),
+        layout: None,
+        module: &shader,
+        entry_point: "main",
+    });
+    let bind_groups = [0, 1].map(|i| {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Rule 30 Bind Group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: cells[i].as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: cells[1 - i].as_entire_binding(),
+                },
+            ],
+        })
+    });
+    Rule30Data {
+        pipeline,
+        cells,
+        bind_groups,
+        current: 0,
+    }
+}
+
+const RULE30_SHADER: &str = r"
+@group(0) @binding(0)
+var<storage, read> input: array<u32>;
+@group(0) @binding(1)
+var<storage, read_write> output: array<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let width = arrayLength(&input);
+    let x = id.x;
+    let left = input[(x + width - 1u) % width];
+    let center = input[x];
+    let right = input[(x + 1u) % width];
+    let pattern = (left << 2u) | (center << 1u) | right;
+    output[x] = (30u >> pattern) & 1u;
+}
+";
@@ -2,9 +2,7 @@ use egui::FullOutput;
 use egui_demo_lib::DemoWindows;
 use egui_wgpu::renderer::ScreenDescriptor;
 use egui_winit::EventResponse;
-use glass::{
-    window::GlassWindow, Glass, GlassApp, GlassConfig, GlassContext, GlassError, RenderData,
-};
+use glass::{Glass, GlassApp, GlassConfig, GlassContext, GlassError, RenderData};
 use wgpu::{CommandEncoder, TextureView};
 use winit::{
     event::Event,
@@ -16,8 +14,10 @@ fn main() -> Result<(), GlassError> {
 }
 
 impl GlassApp for GuiApp {
-    fn start(&mut self, event_loop: &EventLoop<()>, context: &mut GlassContext) {
-        initialize_gui_app(self, context, event_loop);
+    fn start(&mut self, event_loop: Option<&EventLoop<()>>, context: &mut GlassContext) {
+        if let Some(event_loop) = event_loop {
+            initialize_gui_app(self, context, event_loop);
+        }
     }
 
     fn input(
@@ -59,8 +59,12 @@ fn initialize_gui_app(
     event_loop: &EventLoopWindowTarget<()>,
 ) {
     let mut egui_winit = egui_winit::State::new(event_loop);
-    let renderer =
-        egui_wgpu::Renderer::new(context.device(), GlassWindow::surface_format(), None, 1);
+    let renderer = egui_wgpu::Renderer::new(
+        context.device(),
+        context.primary_render_window().surface_format(),
+        None,
+        1,
+    );
 
     egui_winit.set_max_texture_side(context.device().limits().max_texture_dimension_2d as usize);
     let pixels_per_point = context.primary_render_window().window().scale_factor() as f32;
@@ -100,9 +104,7 @@ fn render(app: &mut GuiApp, context: &GlassContext, render_data: RenderData) {
         frame,
         ..
     } = render_data;
-    let view = frame
-        .texture
-        .create_view(&wgpu::TextureViewDescriptor::default());
+    let view = frame.create_view(&wgpu::TextureViewDescriptor::default());
 
     render_egui(app, context, encoder, &view);
 }
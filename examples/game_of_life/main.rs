@@ -1,13 +1,10 @@
-use std::{borrow::Cow, time::Instant};
+use std::borrow::Cow;
 
 use bytemuck::{Pod, Zeroable};
 use glam::Vec2;
 use glass::{
-    device_context::DeviceConfig,
-    pipelines::QuadPipeline,
-    texture::Texture,
-    window::{GlassWindow, WindowConfig},
-    Glass, GlassApp, GlassConfig, GlassContext, GlassError, RenderData,
+    device_context::DeviceConfig, pipelines::QuadPipeline, texture::Texture, window::WindowConfig,
+    ControlFlowPolicy, Glass, GlassApp, GlassConfig, GlassContext, GlassError, RenderData,
 };
 use wgpu::{
     AddressMode, Backends, BindGroup, BindGroupDescriptor, CommandEncoder, ComputePassDescriptor,
@@ -15,14 +12,10 @@ use wgpu::{
     PresentMode, PushConstantRange, SamplerDescriptor, ShaderStages, StorageTextureAccess,
     TextureFormat, TextureUsages,
 };
-use winit::{
-    event::{ElementState, Event, MouseButton, WindowEvent},
-    event_loop::{EventLoop, EventLoopWindowTarget},
-};
+use winit::{event::MouseButton, event_loop::EventLoop};
 
 const WIDTH: u32 = 1024;
 const HEIGHT: u32 = 1024;
-const FPS_60: f32 = 16.0 / 1000.0;
 #[rustfmt::skip]
 const OPENGL_TO_WGPU: glam::Mat4 = glam::Mat4::from_cols_array(&[
     1.0, 0.0, 0.0, 0.0,
@@ -42,6 +35,7 @@ fn config() -> GlassConfig {
                 ..Limits::default()
             },
             backends: Backends::all(),
+            ..DeviceConfig::default()
         },
         window_configs: vec![WindowConfig {
             width: WIDTH,
@@ -50,6 +44,10 @@ fn config() -> GlassConfig {
             present_mode: PresentMode::AutoNoVsync,
             ..WindowConfig::default()
         }],
+        // Simulate at 60 fps regardless of render rate
+        fixed_update_rate: Some(60.0),
+        control_flow_policy: ControlFlowPolicy::Poll,
+        ..GlassConfig::default()
     }
 }
 
@@ -60,15 +58,16 @@ fn main() -> Result<(), GlassError> {
 // Think of this like reading a "table of contents".
 // - Start is run before event loop
 // - Input is run on winit input
+// - Fixed update is run at 60 fps
 // - Update is run every frame
 // - Render is run for each window after update every frame
 impl GlassApp for GameOfLifeApp {
-    fn start(&mut self, _event_loop: &EventLoop<()>, context: &mut GlassContext) {
+    fn start(&mut self, _event_loop: Option<&EventLoop<()>>, context: &mut GlassContext) {
         // Create pipelines
         let (init_pipeline, game_of_life_pipeline, draw_pipeline) =
             create_game_of_life_pipeline(context);
         let quad_pipeline = QuadPipeline::new(context.device(), wgpu::ColorTargetState {
-            format: GlassWindow::surface_format(),
+            format: context.primary_render_window().surface_format(),
             blend: Some(wgpu::BlendState {
                 color: wgpu::BlendComponent::OVER,
                 alpha: wgpu::BlendComponent::OVER,
@@ -88,13 +87,15 @@ impl GlassApp for GameOfLifeApp {
         init_game_of_life(self, context);
     }
 
-    fn input(
-        &mut self,
-        _context: &mut GlassContext,
-        _event_loop: &EventLoopWindowTarget<()>,
-        event: &Event<()>,
-    ) {
-        handle_inputs(self, event);
+    fn fixed_update(&mut self, context: &mut GlassContext) {
+        let mut encoder =
+            context
+                .device()
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Game Of Life Update"),
+                });
+        update_game_of_life(self, context, &mut encoder);
+        context.queue().submit(Some(encoder.finish()));
     }
 
     fn update(&mut self, context: &mut GlassContext) {
@@ -106,6 +107,7 @@ impl GlassApp for GameOfLifeApp {
     }
 }
 
+#[derive(Default)]
 struct GameOfLifeApp {
     quad_pipeline: Option<QuadPipeline>,
     init_pipeline: Option<ComputePipeline>,
@@ -114,34 +116,9 @@ struct GameOfLifeApp {
     data: Option<CanvasData>,
     cursor_pos: Vec2,
     prev_cursor_pos: Option<Vec2>,
-    draw: bool,
-    dt_sum: f32,
-    num_dts: f32,
-    time: Instant,
-    updated_time: Instant,
     count: usize,
 }
 
-impl Default for GameOfLifeApp {
-    fn default() -> Self {
-        Self {
-            quad_pipeline: None,
-            init_pipeline: None,
-            game_of_life_pipeline: None,
-            draw_pipeline: None,
-            data: None,
-            cursor_pos: Default::default(),
-            prev_cursor_pos: None,
-            draw: false,
-            dt_sum: 0.0,
-            num_dts: 0.0,
-            time: Instant::now(),
-            updated_time: Instant::now(),
-            count: 0,
-        }
-    }
-}
-
 impl GameOfLifeApp {
     fn cursor_to_canvas(&self, width: f32, height: f32, scale_factor: f32) -> (Vec2, Vec2) {
         let half_screen = Vec2::new(width, height) / scale_factor / 2.0;
@@ -154,19 +131,13 @@ impl GameOfLifeApp {
 }
 
 fn run_update(app: &mut GameOfLifeApp, context: &mut GlassContext) {
-    let now = Instant::now();
-    app.dt_sum += (now - app.time).as_secs_f32();
-    app.num_dts += 1.0;
-    if app.num_dts == 100.0 {
+    if context.frame_index().is_multiple_of(100) {
         // Set fps
-        context.primary_render_window().window().set_title(&format!(
-            "Game Of Life: {:.2}",
-            1.0 / (app.dt_sum / app.num_dts)
-        ));
-        app.num_dts = 0.0;
-        app.dt_sum = 0.0;
+        context
+            .primary_render_window()
+            .window()
+            .set_title(&format!("Game Of Life: {:.2}", context.fps()));
     }
-    app.time = Instant::now();
 
     // Use only single command queue
     let mut encoder = context
@@ -174,12 +145,11 @@ fn run_update(app: &mut GameOfLifeApp, context: &mut GlassContext) {
         .create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Computes"),
         });
-    // Update 60fps
-    if (app.time - app.updated_time).as_secs_f32() > FPS_60 {
-        update_game_of_life(app, context, &mut encoder);
-        app.updated_time = app.time;
+    let input = context.primary_render_window().input();
+    if let Some(cursor_pos) = input.cursor_position() {
+        app.cursor_pos = cursor_pos;
     }
-    if app.draw {
+    if input.mouse_held(MouseButton::Left) {
         draw_game_of_life(app, context, &mut encoder);
     }
     // Update prev cursor pos
@@ -199,20 +169,16 @@ fn render(app: &mut GameOfLifeApp, render_data: RenderData) {
     let RenderData {
         encoder,
         frame,
-        window,
-        ..
+        target,
     } = render_data;
     let (width, height) = {
-        let scale_factor = window.window().scale_factor() as f32;
-        let size = window.window().inner_size();
+        let scale_factor = target.scale_factor() as f32;
         (
-            size.width as f32 / scale_factor,
-            size.height as f32 / scale_factor,
+            frame.width() as f32 / scale_factor,
+            frame.height() as f32 / scale_factor,
         )
     };
-    let view = frame
-        .texture
-        .create_view(&wgpu::TextureViewDescriptor::default());
+    let view = frame.create_view(&wgpu::TextureViewDescriptor::default());
 
     {
         let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -238,29 +204,6 @@ fn render(app: &mut GameOfLifeApp, render_data: RenderData) {
     }
 }
 
-fn handle_inputs(app: &mut GameOfLifeApp, event: &Event<()>) {
-    if let Event::WindowEvent {
-        event, ..
-    } = event
-    {
-        match event {
-            WindowEvent::CursorMoved {
-                position, ..
-            } => {
-                app.cursor_pos = Vec2::new(position.x as f32, position.y as f32);
-            }
-            WindowEvent::MouseInput {
-                button: MouseButton::Left,
-                state,
-                ..
-            } => {
-                app.draw = state == &ElementState::Pressed;
-            }
-            _ => (),
-        }
-    }
-}
-
 fn draw_game_of_life(
     app: &mut GameOfLifeApp,
     context: &mut GlassContext,
@@ -302,7 +245,7 @@ fn update_game_of_life(
     } = app;
     let data = data.as_ref().unwrap();
     let game_of_life_pipeline = game_of_life_pipeline.as_ref().unwrap();
-    let (canvas, data_in) = if app.count % 2 == 0 {
+    let (canvas, data_in) = if app.count.is_multiple_of(2) {
         (&data.canvas.views[0], &data.data_in.views[0])
     } else {
         (&data.data_in.views[0], &data.canvas.views[0])
@@ -0,0 +1,31 @@
+// Renders the triangle example offscreen
+#[path = "triangle/app.rs"]
+mod app;
+
+use app::TriangleApp;
+use glass::{GlassConfig, GlassError, HeadlessGlass};
+
+const WIDTH: u32 = 256;
+const HEIGHT: u32 = 256;
+const NUM_FRAMES: usize = 10;
+
+fn main() -> Result<(), GlassError> {
+    let mut glass = HeadlessGlass::new(
+        TriangleApp::default(),
+        GlassConfig::performance(WIDTH, HEIGHT),
+    )?;
+    glass.run_frames(NUM_FRAMES)?;
+    let image = glass.read_target(0);
+    glass.end();
+    image?
+        .save("headless_triangle.png")
+        .map_err(|e| GlassError::ImageError {
+            name: "headless_triangle.png".to_owned(),
+            source: e,
+        })?;
+    println!(
+        "Rendered {} frames to headless_triangle.png",
+        glass.frame_count()
+    );
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use glass::{
+    recording::InputRecording, Glass, GlassApp, GlassConfig, GlassContext, GlassError, RenderData,
+};
+use winit::event_loop::EventLoop;
+
+const RECORDING_PATH: &str = "input_recording.ron";
+
+/// Run without args to record input until exit, then run with `replay` arg to replay it.
+fn main() -> Result<(), GlassError> {
+    let replay = std::env::args().any(|arg| arg == "replay");
+    Glass::new(
+        RecordingApp {
+            replay,
+            ..Default::default()
+        },
+        GlassConfig::default(),
+    )
+    .run()
+}
+
+#[derive(Default)]
+struct RecordingApp {
+    replay: bool,
+    clear_color: wgpu::Color,
+}
+
+impl GlassApp for RecordingApp {
+    fn start(&mut self, _event_loop: Option<&EventLoop<()>>, context: &mut GlassContext) {
+        if self.replay {
+            let recording = InputRecording::load(RECORDING_PATH).unwrap();
+            println!("Replaying {} frames", recording.frames.len());
+            context.start_replay(recording);
+        } else {
+            println!("Recording input, move the cursor around");
+            context.start_recording();
+        }
+    }
+
+    fn update(&mut self, context: &mut GlassContext) {
+        // Color by cursor position, which follows the recording when replaying
+        let size = context.primary_render_window().surface_size();
+        if let Some(cursor) = context
+            .primary_input()
+            .and_then(|input| input.cursor_position())
+        {
+            self.clear_color = wgpu::Color {
+                r: (cursor.x / size[0] as f32) as f64,
+                g: (cursor.y / size[1] as f32) as f64,
+                b: 0.5,
+                a: 1.0,
+            };
+        }
+        if self.replay && !context.is_replaying() {
+            println!("Replay finished");
+            context.exit();
+        }
+    }
+
+    fn render(&mut self, _context: &GlassContext, render_data: RenderData) {
+        let RenderData {
+            encoder,
+            frame,
+            ..
+        } = render_data;
+        let view = frame.create_view(&wgpu::TextureViewDescriptor::default());
+        {
+            let _rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.clear_color),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+        }
+    }
+
+    fn end(&mut self, context: &mut GlassContext) {
+        if let Some(recording) = context.stop_recording() {
+            recording.save(RECORDING_PATH).unwrap();
+            println!(
+                "Saved {} frames to {}",
+                recording.frames.len(),
+                RECORDING_PATH
+            );
+        }
+    }
+}
@@ -5,7 +5,7 @@ use wgpu::Color;
 use winit::{
     event::{DeviceEvent, ElementState, Event, VirtualKeyCode},
     event_loop::{EventLoop, EventLoopWindowTarget},
-    window::WindowId,
+    window::{WindowId, WindowLevel},
 };
 
 const WIDTH: u32 = 256;
@@ -30,18 +30,29 @@ struct MultiWindowApp {
 }
 
 impl GlassApp for MultiWindowApp {
-    fn start(&mut self, event_loop: &EventLoop<()>, context: &mut GlassContext) {
-        println!("Press space to create windows, esc to close all but last");
-        self.window_ids.push(
-            context
-                .create_window(event_loop, WindowConfig {
-                    width: WIDTH,
-                    height: HEIGHT,
-                    exit_on_esc: true,
-                    ..WindowConfig::default()
-                })
-                .unwrap(),
-        );
+    fn start(&mut self, event_loop: Option<&EventLoop<()>>, context: &mut GlassContext) {
+        let Some(event_loop) = event_loop else {
+            return;
+        };
+        println!("Press space to create palette windows, esc to close them");
+        context
+            .create_window(event_loop, WindowConfig {
+                width: WIDTH,
+                height: HEIGHT,
+                exit_on_esc: true,
+                ..WindowConfig::default()
+            })
+            .unwrap();
+    }
+
+    fn on_window_created(&mut self, _context: &mut GlassContext, window_id: WindowId) {
+        self.window_ids.push(window_id);
+        println!("Window ids: {:#?}", self.window_ids);
+    }
+
+    fn on_window_closed(&mut self, _context: &mut GlassContext, window_id: WindowId) {
+        self.window_ids.retain(|id| *id != window_id);
+        println!("Window ids: {:#?}", self.window_ids);
     }
 
     fn input(
@@ -57,18 +68,18 @@ impl GlassApp for MultiWindowApp {
         {
             if let Some(key) = input.virtual_keycode {
                 if key == VirtualKeyCode::Space && input.state == ElementState::Pressed {
-                    // Create window
-                    self.window_ids.push(
-                        context
-                            .create_window(event_loop, WindowConfig {
-                                width: WIDTH,
-                                height: HEIGHT,
-                                exit_on_esc: true,
-                                ..WindowConfig::default()
-                            })
-                            .unwrap(),
-                    );
-                    println!("Window ids: {:#?}", self.window_ids);
+                    // Create a palette window that stays on top
+                    context
+                        .create_window(event_loop, WindowConfig {
+                            title: format!("Palette {}", self.window_ids.len()),
+                            width: WIDTH / 2,
+                            height: HEIGHT / 2,
+                            resizable: false,
+                            window_level: WindowLevel::AlwaysOnTop,
+                            exit_on_esc: true,
+                            ..WindowConfig::default()
+                        })
+                        .unwrap();
                 }
             }
         }
@@ -78,15 +89,12 @@ impl GlassApp for MultiWindowApp {
         let RenderData {
             encoder,
             frame,
-            window,
-            ..
+            target,
         } = render_data;
         // Select clear color by window id
-        let clear_color =
-            CLEAR_COLORS[u64::from(window.window().id()) as usize % CLEAR_COLORS.len()];
-        let view = frame
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        let window_id = target.window_id().map_or(0, u64::from);
+        let clear_color = CLEAR_COLORS[window_id as usize % CLEAR_COLORS.len()];
+        let view = frame.create_view(&wgpu::TextureViewDescriptor::default());
         {
             let _rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
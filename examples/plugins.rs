@@ -0,0 +1,47 @@
+use glass::{Glass, GlassApp, GlassConfig, GlassContext, GlassError, GlassPlugin};
+use winit::event_loop::EventLoop;
+
+fn main() -> Result<(), GlassError> {
+    Glass::new(PluginApp, GlassConfig::default())
+        .with_plugin(FpsCounterPlugin)
+        .run()
+}
+
+/// State shared by [`FpsCounterPlugin`] through the context
+#[derive(Default)]
+struct FpsCounter {
+    fps: f64,
+    min_fps: f64,
+}
+
+/// A reusable plugin that keeps [`FpsCounter`] up to date
+struct FpsCounterPlugin;
+
+impl GlassPlugin for FpsCounterPlugin {
+    fn start(&mut self, _event_loop: Option<&EventLoop<()>>, context: &mut GlassContext) {
+        context.insert_state(FpsCounter::default());
+    }
+
+    fn update(&mut self, context: &mut GlassContext) {
+        let fps = context.fps();
+        let max_frame_time = context.frame_timer().max_frame_time().as_secs_f64();
+        let counter = context.state_mut::<FpsCounter>().unwrap();
+        counter.fps = fps;
+        counter.min_fps = if max_frame_time > 0.0 {
+            1.0 / max_frame_time
+        } else {
+            0.0
+        };
+    }
+}
+
+struct PluginApp;
+
+impl GlassApp for PluginApp {
+    fn update(&mut self, context: &mut GlassContext) {
+        // Plugins run before app's update, so the counter is up to date
+        let counter = context.state::<FpsCounter>().unwrap();
+        let title = format!("Fps: {:.0} (min {:.0})", counter.fps, counter.min_fps);
+        context.primary_render_window().window().set_title(&title);
+    }
+}
@@ -1,13 +1,8 @@
 use glass::{
-    device_context::DeviceConfig,
-    pipelines::QuadPipeline,
-    texture::Texture,
-    window::{GlassWindow, WindowConfig},
+    device_context::DeviceConfig, pipelines::QuadPipeline, texture::Texture, window::WindowConfig,
     Glass, GlassApp, GlassConfig, GlassContext, GlassError, RenderData,
 };
-use wgpu::{
-    AddressMode, BindGroup, FilterMode, Limits, SamplerDescriptor, TextureFormat, TextureUsages,
-};
+use wgpu::{AddressMode, BindGroup, FilterMode, SamplerDescriptor, TextureFormat, TextureUsages};
 use winit::event_loop::EventLoop;
 
 const WIDTH: u32 = 1920;
@@ -26,20 +21,14 @@ fn main() -> Result<(), GlassError> {
 
 fn config() -> GlassConfig {
     GlassConfig {
-        device_config: DeviceConfig {
-            limits: Limits {
-                // Needed for push constants
-                max_push_constant_size: 128,
-                ..Default::default()
-            },
-            ..DeviceConfig::performance()
-        },
+        device_config: DeviceConfig::performance(),
         window_configs: vec![WindowConfig {
             width: WIDTH,
             height: HEIGHT,
             exit_on_esc: true,
             ..WindowConfig::default()
         }],
+        ..GlassConfig::default()
     }
 }
 
@@ -51,9 +40,9 @@ struct TreeApp {
 }
 
 impl GlassApp for TreeApp {
-    fn start(&mut self, _event_loop: &EventLoop<()>, context: &mut GlassContext) {
+    fn start(&mut self, _event_loop: Option<&EventLoop<()>>, context: &mut GlassContext) {
         let quad_pipeline = QuadPipeline::new(context.device(), wgpu::ColorTargetState {
-            format: GlassWindow::surface_format(),
+            format: context.primary_render_window().surface_format(),
             blend: Some(wgpu::BlendState {
                 color: wgpu::BlendComponent::OVER,
                 alpha: wgpu::BlendComponent::OVER,
@@ -75,20 +64,16 @@ impl GlassApp for TreeApp {
         let RenderData {
             encoder,
             frame,
-            window,
-            ..
+            target,
         } = render_data;
         let (width, height) = {
-            let scale_factor = window.window().scale_factor() as f32;
-            let size = window.window().inner_size();
+            let scale_factor = target.scale_factor() as f32;
             (
-                size.width as f32 / scale_factor,
-                size.height as f32 / scale_factor,
+                frame.width() as f32 / scale_factor,
+                frame.height() as f32 / scale_factor,
             )
         };
-        let view = frame
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        let view = frame.create_view(&wgpu::TextureViewDescriptor::default());
 
         {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
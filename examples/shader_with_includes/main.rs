@@ -26,7 +26,7 @@ struct TriangleApp {
 }
 
 impl GlassApp for TriangleApp {
-    fn start(&mut self, _event_loop: &EventLoop<()>, context: &mut GlassContext) {
+    fn start(&mut self, _event_loop: Option<&EventLoop<()>>, context: &mut GlassContext) {
         self.triangle_pipeline = Some(create_triangle_pipeline(context));
     }
 
@@ -36,9 +36,7 @@ impl GlassApp for TriangleApp {
             frame,
             ..
         } = render_data;
-        let view = frame
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        let view = frame.create_view(&wgpu::TextureViewDescriptor::default());
         let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: None,
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
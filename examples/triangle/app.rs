@@ -0,0 +1,81 @@
+use std::borrow::Cow;
+
+use glass::{GlassApp, GlassContext, RenderData};
+use wgpu::{
+    MultisampleState, PipelineLayoutDescriptor, PrimitiveState, RenderPipeline,
+    RenderPipelineDescriptor, ShaderModuleDescriptor,
+};
+use winit::event_loop::EventLoop;
+
+// Shared with the headless example
+#[derive(Default)]
+pub struct TriangleApp {
+    triangle_pipeline: Option<RenderPipeline>,
+}
+
+impl GlassApp for TriangleApp {
+    fn start(&mut self, _event_loop: Option<&EventLoop<()>>, context: &mut GlassContext) {
+        self.triangle_pipeline = Some(create_triangle_pipeline(context));
+    }
+
+    fn render(&mut self, _context: &GlassContext, render_data: RenderData) {
+        let RenderData {
+            encoder,
+            frame,
+            ..
+        } = render_data;
+        let view = frame.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::GREEN),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        let triangle_pipeline = self.triangle_pipeline.as_ref().unwrap();
+        rpass.set_pipeline(triangle_pipeline);
+        rpass.draw(0..3, 0..1);
+    }
+}
+
+fn create_triangle_pipeline(context: &mut GlassContext) -> RenderPipeline {
+    let shader = context
+        .device()
+        .create_shader_module(ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("triangle.wgsl"))),
+        });
+    let layout = context
+        .device()
+        .create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+    let pipeline = context
+        .device()
+        .create_render_pipeline(&RenderPipelineDescriptor {
+            label: None,
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(context.primary_surface_format().unwrap().into())],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            multiview: None,
+        });
+    pipeline
+}
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use glass::{
+    ControlFlowPolicy, Glass, GlassApp, GlassConfig, GlassContext, GlassError, RenderData,
+};
+use wgpu::Color;
+use winit::event_loop::EventLoop;
+
+const CLEAR_COLORS: [Color; 3] = [Color::RED, Color::GREEN, Color::BLUE];
+
+fn main() -> Result<(), GlassError> {
+    // Only render when color changes or window receives input
+    let config = GlassConfig {
+        control_flow_policy: ControlFlowPolicy::Reactive,
+        ..GlassConfig::default()
+    };
+    Glass::new(UserEventApp::default(), config).run()
+}
+
+/// Events sent to the app from a background thread
+enum UserEvent {
+    ChangeColor(usize),
+}
+
+#[derive(Default)]
+struct UserEventApp {
+    color_index: usize,
+}
+
+impl GlassApp<UserEvent> for UserEventApp {
+    fn start(&mut self, _event_loop: Option<&EventLoop<UserEvent>>, context: &mut GlassContext) {
+        let proxy = context.event_loop_proxy::<UserEvent>().unwrap();
+        // Change clear color once a second from another thread
+        std::thread::spawn(move || {
+            let mut color_index = 0;
+            loop {
+                std::thread::sleep(Duration::from_secs(1));
+                color_index = (color_index + 1) % CLEAR_COLORS.len();
+                // Event loop has exited
+                if proxy
+                    .send_event(UserEvent::ChangeColor(color_index))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+    }
+
+    fn user_event(&mut self, context: &mut GlassContext, event: UserEvent) {
+        match event {
+            UserEvent::ChangeColor(color_index) => self.color_index = color_index,
+        }
+        let window_id = context.primary_render_window().window().id();
+        context.request_redraw(window_id);
+    }
+
+    fn render(&mut self, _context: &GlassContext, render_data: RenderData) {
+        let RenderData {
+            encoder,
+            frame,
+            ..
+        } = render_data;
+        let view = frame.create_view(&wgpu::TextureViewDescriptor::default());
+        {
+            let _rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(CLEAR_COLORS[self.color_index]),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+        }
+    }
+}
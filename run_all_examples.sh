@@ -4,4 +4,9 @@ cargo run --example shader_with_includes
 cargo run --example quad
 cargo run --example multiple_windows
 cargo run --example game_of_life
+cargo run --example headless
+cargo run --example user_events
+cargo run --example plugins
+cargo run --example input_recording
+cargo run --example input_recording -- replay
 cargo run --example egui_gui --features "egui_gui"
@@ -0,0 +1,447 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use winit::event::{MouseButton, VirtualKeyCode};
+
+use crate::{
+    input::InputState,
+    utils::{load_ron, save_ron},
+    GlassError,
+};
+
+/// A key or a mouse button
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputButton {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+}
+
+impl InputButton {
+    fn held(&self, input: &InputState) -> bool {
+        match self {
+            InputButton::Key(key) => input.key_held(*key),
+            InputButton::Mouse(button) => input.mouse_held(*button),
+        }
+    }
+
+    fn pressed(&self, input: &InputState) -> bool {
+        match self {
+            InputButton::Key(key) => input.key_pressed(*key),
+            InputButton::Mouse(button) => input.mouse_pressed(*button),
+        }
+    }
+
+    fn released(&self, input: &InputState) -> bool {
+        match self {
+            InputButton::Key(key) => input.key_released(*key),
+            InputButton::Mouse(button) => input.mouse_released(*button),
+        }
+    }
+
+    /// Pressed & released within the same frame
+    fn tapped(&self, input: &InputState) -> bool {
+        self.pressed(input) && self.released(input)
+    }
+}
+
+/// Analog mouse input
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MouseAxis {
+    /// Cursor movement in physical pixels
+    CursorX,
+    CursorY,
+    /// Scroll in lines or pixels, see [`InputState::scroll_delta`]
+    ScrollX,
+    ScrollY,
+}
+
+/// Input bound to an action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Binding {
+    Button(InputButton),
+    /// All buttons must be held, e.g. ctrl + s
+    Chord(Vec<InputButton>),
+    /// Value is `-1.0` when negative button is held and `1.0` when positive is held
+    ButtonAxis {
+        negative: InputButton,
+        positive: InputButton,
+    },
+    /// Value is mouse movement this frame multiplied by scale. Held while the value is non zero.
+    Mouse {
+        axis: MouseAxis,
+        scale: f32,
+    },
+}
+
+impl Binding {
+    fn held(&self, input: &InputState) -> bool {
+        match self {
+            Binding::Button(button) => button.held(input),
+            Binding::Chord(buttons) => {
+                !buttons.is_empty() && buttons.iter().all(|button| button.held(input))
+            }
+            Binding::ButtonAxis {
+                negative,
+                positive,
+            } => negative.held(input) || positive.held(input),
+            Binding::Mouse {
+                ..
+            } => self.value(input) != 0.0,
+        }
+    }
+
+    /// Pressed & released within the same frame. A chord is tapped when one of its buttons is
+    /// tapped while the others are held or tapped too.
+    fn tapped(&self, input: &InputState) -> bool {
+        match self {
+            Binding::Button(button) => button.tapped(input),
+            Binding::Chord(buttons) => {
+                buttons.iter().any(|button| button.tapped(input))
+                    && buttons
+                        .iter()
+                        .all(|button| button.held(input) || button.tapped(input))
+            }
+            Binding::ButtonAxis {
+                negative,
+                positive,
+            } => negative.tapped(input) || positive.tapped(input),
+            Binding::Mouse {
+                ..
+            } => false,
+        }
+    }
+
+    fn value(&self, input: &InputState) -> f32 {
+        match self {
+            Binding::Button(_) | Binding::Chord(_) => {
+                if self.held(input) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Binding::ButtonAxis {
+                negative,
+                positive,
+            } => positive.held(input) as i32 as f32 - negative.held(input) as i32 as f32,
+            Binding::Mouse {
+                axis,
+                scale,
+            } => {
+                let value = match axis {
+                    MouseAxis::CursorX => input.cursor_delta().x,
+                    MouseAxis::CursorY => input.cursor_delta().y,
+                    MouseAxis::ScrollX => input.scroll_delta().x,
+                    MouseAxis::ScrollY => input.scroll_delta().y,
+                };
+                value * scale
+            }
+        }
+    }
+}
+
+/// State of an action this frame
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct ActionState {
+    pub held: bool,
+    pub pressed: bool,
+    pub released: bool,
+    /// Analog value, largest magnitude of the action's bindings. `1.0` for held buttons.
+    pub value: f32,
+}
+
+/// Named actions bound to keys, mouse buttons, chords or axes. Updated from window input by
+/// [`Glass`](crate::Glass) each frame before
+/// [`GlassApp::fixed_update`](crate::GlassApp::fixed_update), access it through
+/// [`GlassContext::actions`](crate::GlassContext::actions).
+///
+/// Bindings can be saved to & loaded from [ron](https://github.com/ron-rs/ron) files.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ActionMap {
+    bindings: BTreeMap<String, Vec<Binding>>,
+    #[serde(skip)]
+    states: HashMap<String, ActionState>,
+    #[serde(skip)]
+    rebind_action: Option<String>,
+    // Action & the button it was rebound to, idle until the button is let go
+    #[serde(skip)]
+    rebound: Option<(String, InputButton)>,
+}
+
+impl ActionMap {
+    /// Add binding to an action. An action can have multiple bindings.
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        self.bindings
+            .entry(action.to_owned())
+            .or_default()
+            .push(binding);
+    }
+
+    /// Replace all bindings of an action
+    pub fn set_bindings(&mut self, action: &str, bindings: Vec<Binding>) {
+        self.bindings.insert(action.to_owned(), bindings);
+    }
+
+    /// Remove all bindings of an action
+    pub fn unbind(&mut self, action: &str) {
+        self.bindings.remove(action);
+        self.states.remove(action);
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.bindings.get(action).map_or(&[], |b| b.as_slice())
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.bindings.keys().map(|action| action.as_str())
+    }
+
+    /// Replace bindings of the action with the next key or mouse button pressed. Frames where
+    /// more than one is pressed are ignored. The action stays idle until the button is let go, so
+    /// the press doesn't trigger it.
+    pub fn rebind_on_next_press(&mut self, action: &str) {
+        self.rebind_action = Some(action.to_owned());
+    }
+
+    /// Whether [`ActionMap::rebind_on_next_press`] is waiting for a press
+    pub fn is_rebinding(&self) -> bool {
+        self.rebind_action.is_some()
+    }
+
+    pub fn state(&self, action: &str) -> ActionState {
+        self.states.get(action).copied().unwrap_or_default()
+    }
+
+    /// Whether action is held down
+    pub fn held(&self, action: &str) -> bool {
+        self.state(action).held
+    }
+
+    /// Whether action started this frame
+    pub fn pressed(&self, action: &str) -> bool {
+        self.state(action).pressed
+    }
+
+    /// Whether action ended this frame
+    pub fn released(&self, action: &str) -> bool {
+        self.state(action).released
+    }
+
+    /// Analog value of action, e.g. in range `[-1, 1]` for button axes
+    pub fn value(&self, action: &str) -> f32 {
+        self.state(action).value
+    }
+
+    /// Update action states from input of windows. An action is held if it's held in any window.
+    pub fn update<'a>(&mut self, inputs: impl Iterator<Item = &'a InputState> + Clone) {
+        if let Some((_, button)) = &self.rebound {
+            if !inputs.clone().any(|input| button.held(input)) {
+                self.rebound = None;
+            }
+        }
+        if let Some(action) = self.rebind_action.clone() {
+            if let Some(button) = inputs.clone().find_map(only_pressed_button) {
+                self.set_bindings(&action, vec![Binding::Button(button)]);
+                self.states.remove(&action);
+                self.rebind_action = None;
+                self.rebound = Some((action, button));
+            }
+        }
+        for (action, bindings) in self.bindings.iter() {
+            if self
+                .rebound
+                .as_ref()
+                .is_some_and(|(rebound, _)| rebound == action)
+            {
+                continue;
+            }
+            let mut held = false;
+            let mut tapped = false;
+            let mut value = 0.0f32;
+            for input in inputs.clone() {
+                for binding in bindings {
+                    held |= binding.held(input);
+                    tapped |= binding.tapped(input);
+                    let binding_value = binding.value(input);
+                    if binding_value.abs() > value.abs() {
+                        value = binding_value;
+                    }
+                }
+            }
+            let prev = self.states.get(action).copied().unwrap_or_default();
+            self.states.insert(action.clone(), ActionState {
+                held,
+                pressed: (held && !prev.held) || tapped,
+                released: (!held && prev.held) || tapped,
+                value,
+            });
+        }
+    }
+
+    /// Load bindings from a ron file
+    pub fn load(path: impl AsRef<Path>) -> Result<ActionMap, GlassError> {
+        load_ron(path)
+    }
+
+    /// Save bindings to a ron file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), GlassError> {
+        save_ron(self, path)
+    }
+}
+
+/// The key or mouse button pressed this frame, if exactly one was
+fn only_pressed_button(input: &InputState) -> Option<InputButton> {
+    let mut pressed = input
+        .keys_pressed()
+        .map(|key| InputButton::Key(*key))
+        .chain(
+            input
+                .mouse_buttons_pressed()
+                .map(|button| InputButton::Mouse(*button)),
+        );
+    match (pressed.next(), pressed.next()) {
+        (Some(button), None) => Some(button),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+    use winit::event::{MouseButton, VirtualKeyCode};
+
+    use crate::{
+        actions::{ActionMap, Binding, InputButton, MouseAxis},
+        input::InputState,
+    };
+
+    #[test]
+    fn test_button_action() {
+        let mut actions = ActionMap::default();
+        actions.bind(
+            "jump",
+            Binding::Button(InputButton::Key(VirtualKeyCode::Space)),
+        );
+        actions.bind(
+            "jump",
+            Binding::Button(InputButton::Mouse(MouseButton::Left)),
+        );
+        let mut input = InputState::default();
+        input.press_key(VirtualKeyCode::Space);
+        actions.update([&input].into_iter());
+        assert!(actions.pressed("jump") && actions.held("jump"));
+        assert_eq!(actions.value("jump"), 1.0);
+        input.end_frame();
+        actions.update([&input].into_iter());
+        assert!(!actions.pressed("jump") && actions.held("jump"));
+        input.release_key(VirtualKeyCode::Space);
+        actions.update([&input].into_iter());
+        assert!(actions.released("jump") && !actions.held("jump"));
+        // Tap within a single frame
+        input.end_frame();
+        input.press_mouse(MouseButton::Left);
+        input.release_mouse(MouseButton::Left);
+        actions.update([&input].into_iter());
+        assert!(actions.pressed("jump") && actions.released("jump"));
+    }
+
+    #[test]
+    fn test_chord_and_axis_actions() {
+        let mut actions = ActionMap::default();
+        actions.bind(
+            "save",
+            Binding::Chord(vec![
+                InputButton::Key(VirtualKeyCode::LControl),
+                InputButton::Key(VirtualKeyCode::S),
+            ]),
+        );
+        actions.bind("move", Binding::ButtonAxis {
+            negative: InputButton::Key(VirtualKeyCode::A),
+            positive: InputButton::Key(VirtualKeyCode::D),
+        });
+        actions.bind("pan", Binding::Mouse {
+            axis: MouseAxis::CursorX,
+            scale: 0.5,
+        });
+        let mut input = InputState::default();
+        input.press_key(VirtualKeyCode::S);
+        input.press_key(VirtualKeyCode::A);
+        input.move_cursor(Vec2::ZERO);
+        input.move_cursor(Vec2::new(10.0, 0.0));
+        actions.update([&input].into_iter());
+        assert!(!actions.held("save"));
+        assert_eq!(actions.value("move"), -1.0);
+        assert_eq!(actions.value("pan"), 5.0);
+        input.end_frame();
+        input.press_key(VirtualKeyCode::LControl);
+        input.press_key(VirtualKeyCode::D);
+        actions.update([&input].into_iter());
+        assert!(actions.pressed("save"));
+        assert_eq!(actions.value("move"), 0.0);
+        assert!(!actions.held("pan"));
+        // Taps within a frame register for chords & axes too
+        let mut input = InputState::default();
+        actions.update([&input].into_iter());
+        input.press_key(VirtualKeyCode::LControl);
+        actions.update([&input].into_iter());
+        input.end_frame();
+        input.press_key(VirtualKeyCode::S);
+        input.release_key(VirtualKeyCode::S);
+        input.press_key(VirtualKeyCode::D);
+        input.release_key(VirtualKeyCode::D);
+        actions.update([&input].into_iter());
+        assert!(actions.pressed("save") && actions.released("save"));
+        assert!(actions.pressed("move") && actions.released("move"));
+    }
+
+    #[test]
+    fn test_rebind_and_serialize() {
+        let mut actions = ActionMap::default();
+        actions.bind(
+            "jump",
+            Binding::Button(InputButton::Key(VirtualKeyCode::Space)),
+        );
+        actions.rebind_on_next_press("jump");
+        let mut input = InputState::default();
+        input.press_key(VirtualKeyCode::J);
+        input.press_key(VirtualKeyCode::K);
+        actions.update([&input].into_iter());
+        // Ambiguous presses are ignored
+        assert!(actions.is_rebinding());
+        let mut input = InputState::default();
+        input.press_key(VirtualKeyCode::J);
+        actions.update([&input].into_iter());
+        assert!(!actions.is_rebinding());
+        assert_eq!(actions.bindings("jump"), &[Binding::Button(
+            InputButton::Key(VirtualKeyCode::J)
+        )]);
+        // The press that rebound the action doesn't trigger it
+        assert!(!actions.pressed("jump"));
+        input.end_frame();
+        actions.update([&input].into_iter());
+        assert!(!actions.pressed("jump") && !actions.held("jump"));
+        input.release_key(VirtualKeyCode::J);
+        actions.update([&input].into_iter());
+        assert!(!actions.released("jump"));
+        input.end_frame();
+        input.press_key(VirtualKeyCode::J);
+        actions.update([&input].into_iter());
+        assert!(actions.pressed("jump"));
+        // Taps within a frame are captured too
+        actions.rebind_on_next_press("jump");
+        let mut input = InputState::default();
+        input.press_mouse(MouseButton::Left);
+        input.release_mouse(MouseButton::Left);
+        actions.update([&input].into_iter());
+        assert!(!actions.pressed("jump"));
+        assert_eq!(actions.bindings("jump"), &[Binding::Button(
+            InputButton::Mouse(MouseButton::Left)
+        )]);
+        let source = ron::to_string(&actions).unwrap();
+        let loaded: ActionMap = ron::from_str(&source).unwrap();
+        assert_eq!(loaded.bindings("jump"), actions.bindings("jump"));
+    }
+}
@@ -0,0 +1,231 @@
+use wgpu::CommandEncoder;
+
+use crate::{
+    device_context::{DeviceConfig, DeviceContext},
+    GlassError,
+};
+
+/// Compute work run step by step by [`ComputeRunner`], without windows or an event loop. For
+/// tools that only dispatch compute shaders, e.g. simulations written to disk, in CLIs and tests.
+pub trait ComputeApp {
+    /// Run once before the first step, e.g. to create pipelines & buffers
+    fn start(&mut self, _context: &DeviceContext) -> Result<(), GlassError> {
+        Ok(())
+    }
+    /// Record the compute passes of a step into `encoder`. The commands are submitted after this.
+    fn step(
+        &mut self,
+        _context: &DeviceContext,
+        _encoder: &mut CommandEncoder,
+    ) -> Result<(), GlassError> {
+        Ok(())
+    }
+    /// Run after the commands of a step have finished on the gpu, e.g. to read buffers back with
+    /// [`DeviceContext::read_buffer`]
+    fn after_step(&mut self, _context: &DeviceContext) -> Result<(), GlassError> {
+        Ok(())
+    }
+    /// Run after the device was lost and has been recreated, to rebuild pipelines & buffers
+    fn device_recreated(&mut self, _context: &DeviceContext) -> Result<(), GlassError> {
+        Ok(())
+    }
+    /// Run once when the runner ends
+    fn end(&mut self, _context: &DeviceContext) {}
+}
+
+/// [`ComputeRunner`] runs a [`ComputeApp`] on a [`DeviceContext`] created without winit. Each
+/// step records the app's commands, submits them and waits for them to finish.
+pub struct ComputeRunner<A: ComputeApp> {
+    app: A,
+    context: DeviceContext,
+    step_count: u64,
+    ended: bool,
+}
+
+impl<A: ComputeApp> ComputeRunner<A> {
+    /// Creates the device, then runs `start`. If `start` fails, `end` is run and the error is
+    /// returned.
+    pub fn new(mut app: A, config: DeviceConfig) -> Result<ComputeRunner<A>, GlassError> {
+        let context = DeviceContext::new_headless(&config)?;
+        if let Err(e) = app.start(&context) {
+            app.end(&context);
+            return Err(e);
+        }
+        Ok(ComputeRunner {
+            app,
+            context,
+            step_count: 0,
+            ended: false,
+        })
+    }
+
+    /// Run a single step. Does nothing once ended. If the app fails, `end` is run and the error
+    /// is returned.
+    pub fn step(&mut self) -> Result<(), GlassError> {
+        if self.ended {
+            return Ok(());
+        }
+        let result = self.run_step();
+        if result.is_err() {
+            self.end();
+        }
+        result
+    }
+
+    fn run_step(&mut self) -> Result<(), GlassError> {
+        if self.context.is_lost() {
+            self.context.recreate(None)?;
+            self.app.device_recreated(&self.context)?;
+        }
+        let mut encoder =
+            self.context
+                .device()
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Compute Commands"),
+                });
+        self.app.step(&self.context, &mut encoder)?;
+        let submission = self.context.submit(Some(encoder.finish()))?;
+        self.context.wait_for(submission)?;
+        self.step_count += 1;
+        self.app.after_step(&self.context)
+    }
+
+    /// Run `num_steps` steps, or until the app fails.
+    pub fn run_steps(&mut self, num_steps: usize) -> Result<(), GlassError> {
+        for _ in 0..num_steps {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    /// Run `end`. Stepping after this does nothing.
+    pub fn end(&mut self) {
+        if !self.ended {
+            self.app.end(&self.context);
+            self.ended = true;
+        }
+    }
+
+    pub fn has_ended(&self) -> bool {
+        self.ended
+    }
+
+    /// Number of steps run so far.
+    pub fn step_count(&self) -> u64 {
+        self.step_count
+    }
+
+    pub fn app(&self) -> &A {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut A {
+        &mut self.app
+    }
+
+    pub fn context(&self) -> &DeviceContext {
+        &self.context
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::{util::DeviceExt, BindGroup, Buffer, BufferUsages, ComputePipeline};
+
+    use super::*;
+
+    const DOUBLE_SHADER: &str = "
+@group(0) @binding(0) var<storage, read_write> values: array<u32>;
+
+@compute @workgroup_size(4)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    values[id.x] = values[id.x] * 2u;
+}
+";
+
+    #[derive(Default)]
+    struct DoubleApp {
+        resources: Option<(ComputePipeline, BindGroup, Buffer)>,
+        read: Vec<u32>,
+        ended: bool,
+    }
+
+    impl ComputeApp for DoubleApp {
+        fn start(&mut self, context: &DeviceContext) -> Result<(), GlassError> {
+            let device = context.device();
+            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Double Shader"),
+                source: wgpu::ShaderSource::Wgsl(DOUBLE_SHADER.into()),
+            });
+            let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Double Pipeline"),
+                layout: None,
+                module: &shader,
+                entry_point: "main",
+            });
+            let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Values"),
+                contents: bytemuck::cast_slice(&[1u32, 2, 3, 4]),
+                usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Values Bind Group"),
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            });
+            self.resources = Some((pipeline, bind_group, buffer));
+            Ok(())
+        }
+
+        fn step(
+            &mut self,
+            _context: &DeviceContext,
+            encoder: &mut CommandEncoder,
+        ) -> Result<(), GlassError> {
+            let (pipeline, bind_group, _) = self.resources.as_ref().unwrap();
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Double Pass"),
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.dispatch_workgroups(1, 1, 1);
+            Ok(())
+        }
+
+        fn after_step(&mut self, context: &DeviceContext) -> Result<(), GlassError> {
+            let (_, _, buffer) = self.resources.as_ref().unwrap();
+            self.read = context.read_buffer_as(buffer)?;
+            Ok(())
+        }
+
+        fn end(&mut self, _context: &DeviceContext) {
+            self.ended = true;
+        }
+    }
+
+    #[test]
+    fn test_compute_runner_reads_back_steps() {
+        // Needs an adapter
+        let Ok(mut runner) = ComputeRunner::new(DoubleApp::default(), DeviceConfig::default())
+        else {
+            return;
+        };
+        runner.step().unwrap();
+        assert_eq!(runner.app().read, vec![2, 4, 6, 8]);
+        runner.run_steps(2).unwrap();
+        assert_eq!(runner.step_count(), 3);
+        assert_eq!(runner.app().read, vec![8, 16, 24, 32]);
+
+        let (_, _, buffer) = runner.app().resources.as_ref().unwrap();
+        let bytes = runner.context().read_buffer(buffer).unwrap();
+        assert_eq!(bytes, bytemuck::cast_slice::<u32, u8>(&[8, 16, 24, 32]));
+
+        runner.end();
+        assert!(runner.app().ended && runner.has_ended());
+        runner.step().unwrap();
+        assert_eq!(runner.step_count(), 3);
+    }
+}
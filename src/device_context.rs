@@ -1,17 +1,157 @@
+use std::{
+    convert::Infallible,
+    error::Error,
+    panic::{self, AssertUnwindSafe},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use bytemuck::Pod;
 use wgpu::{
-    Adapter, Backends, Device, DeviceDescriptor, Instance, InstanceDescriptor, Limits,
-    PowerPreference, Queue, RequestAdapterOptions, Surface,
+    Adapter, AdapterInfo, Backend, Backends, Buffer, BufferUsages, CommandBuffer, Device,
+    DeviceDescriptor, DeviceType, ErrorFilter, Instance, InstanceDescriptor, Limits, Maintain,
+    MapMode, PowerPreference, Queue, RequestAdapterOptions, SubmissionIndex, Surface,
+};
+use wgpu_core::{
+    binding_model::{CreateBindGroupError, CreateBindGroupLayoutError, CreatePipelineLayoutError},
+    device::{
+        queue::{QueueSubmitError, QueueWriteError},
+        DeviceError,
+    },
+    pipeline::{CreateComputePipelineError, CreateRenderPipelineError, CreateShaderModuleError},
+    present::{ConfigureSurfaceError, SurfaceError},
+    resource::{
+        BufferAccessError, CreateBufferError, CreateQuerySetError, CreateSamplerError,
+        CreateTextureError,
+    },
+};
+use winit::window::{Window, WindowId};
+
+use crate::{
+    pipelines::upload_constants, utils::wait_async, window::WindowConfig, GlassError, GlassStage,
 };
-use winit::window::Window;
 
-use crate::{utils::wait_async, window::WindowConfig, GlassError};
+/// How wgpu errors, e.g. validation errors, are handled. Errors captured around a stage of the
+/// app are reported as [`GlassError::GpuError`] with the stage & window they happened in.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum GpuErrorMode {
+    /// Panic like wgpu's default error handler
+    #[default]
+    Panic,
+    /// Print errors to stderr and continue
+    Log,
+    /// Collect errors, take them with [`DeviceContext::take_errors`]
+    Collect,
+}
+
+/// Environment variable overriding [`DeviceConfig::adapter`], parsed with
+/// [`AdapterSelector::from_str`]. E.g. `GLASS_ADAPTER=llvmpipe` or `GLASS_ADAPTER=discrete`.
+pub const ADAPTER_ENV_VAR: &str = "GLASS_ADAPTER";
+
+/// Selects an adapter among those listed by [`DeviceContext::enumerate_adapters`]. The first
+/// matching adapter compatible with the first window is chosen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdapterSelector {
+    /// Adapter whose name contains the string, ignoring case
+    Name(String),
+    /// Adapter with pci vendor & device id
+    Id {
+        vendor: usize,
+        device: usize,
+    },
+    DeviceType(DeviceType),
+    /// Index in [`DeviceContext::enumerate_adapters`]
+    Index(usize),
+    /// Software fallback adapter, see [`RequestAdapterOptions::force_fallback_adapter`]
+    Fallback,
+}
+
+impl AdapterSelector {
+    /// Whether adapter at `index` of [`DeviceContext::enumerate_adapters`] matches
+    pub fn matches(&self, index: usize, info: &AdapterInfo) -> bool {
+        match self {
+            AdapterSelector::Name(name) => info.name.to_lowercase().contains(&name.to_lowercase()),
+            AdapterSelector::Id {
+                vendor,
+                device,
+            } => info.vendor == *vendor && info.device == *device,
+            AdapterSelector::DeviceType(device_type) => info.device_type == *device_type,
+            AdapterSelector::Index(i) => index == *i,
+            AdapterSelector::Fallback => info.device_type == DeviceType::Cpu,
+        }
+    }
+
+    /// Selector from [`ADAPTER_ENV_VAR`], if set
+    pub fn from_env() -> Option<AdapterSelector> {
+        std::env::var(ADAPTER_ENV_VAR)
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.parse().unwrap())
+    }
+}
+
+impl FromStr for AdapterSelector {
+    type Err = Infallible;
+
+    /// Parses an index (`1`), a hex vendor & device id (`10de:2204`), a device type (`discrete`,
+    /// `integrated`, `virtual`, `cpu` or `other`) or `fallback`. Anything else is an adapter name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(index) = s.parse() {
+            return Ok(AdapterSelector::Index(index));
+        }
+        if let Some((vendor, device)) = s.split_once(':') {
+            let parse_hex = |id: &str| usize::from_str_radix(id.trim_start_matches("0x"), 16);
+            if let (Ok(vendor), Ok(device)) = (parse_hex(vendor), parse_hex(device)) {
+                return Ok(AdapterSelector::Id {
+                    vendor,
+                    device,
+                });
+            }
+        }
+        let selector = match s.to_lowercase().as_str() {
+            "discrete" => AdapterSelector::DeviceType(DeviceType::DiscreteGpu),
+            "integrated" => AdapterSelector::DeviceType(DeviceType::IntegratedGpu),
+            "virtual" => AdapterSelector::DeviceType(DeviceType::VirtualGpu),
+            "cpu" => AdapterSelector::DeviceType(DeviceType::Cpu),
+            "other" => AdapterSelector::DeviceType(DeviceType::Other),
+            "fallback" => AdapterSelector::Fallback,
+            _ => AdapterSelector::Name(s.to_owned()),
+        };
+        Ok(selector)
+    }
+}
+
+/// Adapter listed by [`DeviceContext::enumerate_adapters`]
+#[derive(Debug, Clone)]
+pub struct AdapterDescription {
+    pub index: usize,
+    pub info: AdapterInfo,
+    pub features: wgpu::Features,
+    pub limits: Limits,
+}
 
 #[derive(Debug, Clone)]
 pub struct DeviceConfig {
     pub power_preference: PowerPreference,
+    /// Features the device must have, device creation fails without them
     pub features: wgpu::Features,
+    /// Features requested if the adapter supports them. Check what was granted with
+    /// [`DeviceContext::features`].
+    pub optional_features: wgpu::Features,
+    /// Limits the device must support, device creation fails without them
     pub limits: Limits,
+    /// Better limits requested as far as the adapter supports them. Check what was granted with
+    /// [`DeviceContext::limits`].
+    pub optional_limits: Limits,
     pub backends: Backends,
+    pub error_mode: GpuErrorMode,
+    /// Adapter to use instead of the one chosen by power preference. Overridden by
+    /// [`ADAPTER_ENV_VAR`].
+    pub adapter: Option<AdapterSelector>,
 }
 
 impl DeviceConfig {
@@ -19,8 +159,12 @@ impl DeviceConfig {
         DeviceConfig {
             power_preference: PowerPreference::HighPerformance,
             features: wgpu::Features::empty(),
+            optional_features: wgpu::Features::empty(),
             limits: Limits::default(),
+            optional_limits: Limits::default(),
             backends: Backends::all(),
+            error_mode: GpuErrorMode::default(),
+            adapter: None,
         }
     }
 }
@@ -30,8 +174,12 @@ impl Default for DeviceConfig {
         DeviceConfig {
             power_preference: PowerPreference::default(),
             features: wgpu::Features::empty(),
+            optional_features: wgpu::Features::empty(),
             limits: Limits::default(),
+            optional_limits: Limits::default(),
             backends: Backends::all(),
+            error_mode: GpuErrorMode::default(),
+            adapter: None,
         }
     }
 }
@@ -43,6 +191,10 @@ pub struct DeviceContext {
     adapter: Adapter,
     device: Device,
     queue: Queue,
+    lost: Arc<AtomicBool>,
+    errors: Arc<Mutex<Vec<GlassError>>>,
+    // One entry per open error scope, holding an error captured on a device since replaced
+    error_scopes: Mutex<Vec<Option<wgpu::Error>>>,
 }
 
 unsafe impl Send for DeviceContext {}
@@ -54,82 +206,230 @@ impl DeviceContext {
         config: &DeviceConfig,
         initial_windows: &[(WindowConfig, Window)],
     ) -> Result<DeviceContext, GlassError> {
-        let instance = Instance::new(InstanceDescriptor {
-            backends: config.backends,
-            ..Default::default()
-        });
+        let instance = Self::create_instance(config);
         // Ensure render context is compatible with our window...
         let surface_maybe = if let Some((_c, w)) = initial_windows.first() {
             Some(unsafe {
                 match instance.create_surface(&w) {
                     Ok(s) => s,
-                    Err(e) => return Err(GlassError::SurfaceError(e)),
+                    Err(e) => {
+                        return Err(GlassError::SurfaceError {
+                            window_id: w.id(),
+                            source: e,
+                        })
+                    }
                 }
             })
         } else {
             None
         };
-        let (adapter, device, queue) = match Self::create_adapter_device_and_queue(
-            config,
-            &instance,
-            surface_maybe.as_ref(),
-        ) {
-            Ok(adq) => adq,
-            Err(e) => return Err(e),
-        };
+        let (adapter, device, queue) =
+            Self::create_adapter_device_and_queue(config, &instance, surface_maybe.as_ref())?;
+        let errors = Arc::new(Mutex::new(vec![]));
+        let lost = set_error_handler(&device, config.error_mode, &errors);
         Ok(Self {
             config: config.clone(),
             instance,
             adapter,
             device,
             queue,
+            lost,
+            errors,
+            error_scopes: Mutex::new(vec![]),
         })
     }
 
+    /// Create a device without windows, e.g. for compute only tools. Never touches winit. See also
+    /// [`ComputeRunner`](crate::ComputeRunner).
+    pub fn new_headless(config: &DeviceConfig) -> Result<DeviceContext, GlassError> {
+        Self::new(config, &[])
+    }
+
     /// If adapter, device and queue has been created without a window (surface), recreate them
     /// once you have a surface to ensure compatibility of queue families.
     pub fn reconfigure_with_surface(&mut self, surface: &Surface) -> Result<(), GlassError> {
-        let (adapter, device, queue) = match Self::create_adapter_device_and_queue(
-            &self.config,
-            &self.instance,
-            Some(surface),
-        ) {
-            Ok(adq) => adq,
-            Err(e) => return Err(e),
-        };
+        let (adapter, device, queue) =
+            Self::create_adapter_device_and_queue(&self.config, &self.instance, Some(surface))?;
+        self.replace_device(adapter, device, queue);
+        Ok(())
+    }
+
+    /// Switch to a new device. Open error scopes are moved to it, errors captured on the old device
+    /// are reported when their scope is popped.
+    fn replace_device(&mut self, adapter: Adapter, device: Device, queue: Queue) {
+        let error_scopes = self.error_scopes.get_mut().unwrap();
+        // Innermost scope is popped first
+        for captured in error_scopes.iter_mut().rev() {
+            let error = wait_async(self.device.pop_error_scope());
+            // Loss of the old device doesn't concern the new one
+            if captured.is_none() && !error.as_ref().is_some_and(is_device_lost_error) {
+                *captured = error;
+            }
+        }
+        for _ in error_scopes.iter() {
+            device.push_error_scope(ErrorFilter::Validation);
+        }
+        let lost = self.is_lost();
+        self.lost = set_error_handler(&device, self.config.error_mode, &self.errors);
         self.adapter = adapter;
-        self.device = device;
+        let old_device = std::mem::replace(&mut self.device, device);
         self.queue = queue;
+        // Dropping a device polls it, which panics if it was lost
+        if lost {
+            std::mem::forget(old_device);
+        }
+    }
+
+    /// Whether the device has been lost, e.g. due to a driver reset or a removed adapter.
+    /// [`Glass`](crate::Glass) recreates the device at the start of the next frame.
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Acquire)
+    }
+
+    /// Capture validation errors until [`DeviceContext::pop_error_scope`]
+    pub fn push_error_scope(&self) {
+        self.error_scopes.lock().unwrap().push(None);
+        self.device.push_error_scope(ErrorFilter::Validation);
+    }
+
+    /// Handle the validation error captured since [`DeviceContext::push_error_scope`] according
+    /// to [`DeviceConfig::error_mode`], reporting the stage & window it happened in. Does nothing
+    /// without an open scope.
+    pub fn pop_error_scope(&self, stage: GlassStage, window_id: Option<WindowId>) {
+        let Some(captured) = self.error_scopes.lock().unwrap().pop() else {
+            return;
+        };
+        let error = wait_async(self.device.pop_error_scope());
+        for error in captured.into_iter().chain(error) {
+            if is_device_lost_error(&error) {
+                self.lost.store(true, Ordering::Release);
+            } else {
+                handle_error(self.config.error_mode, &self.errors, GlassError::GpuError {
+                    stage: Some(stage),
+                    window_id,
+                    message: error.to_string(),
+                });
+            }
+        }
+    }
+
+    /// Take errors collected with [`GpuErrorMode::Collect`]
+    pub fn take_errors(&self) -> Vec<GlassError> {
+        std::mem::take(&mut *self.errors.lock().unwrap())
+    }
+
+    /// Recreate adapter, device & queue after the device was lost. With a `window`, the instance
+    /// is recreated too and the adapter is chosen to be compatible with it. Surfaces of the old
+    /// instance must then be recreated.
+    pub(crate) fn recreate(&mut self, window: Option<&Window>) -> Result<(), GlassError> {
+        let Some(window) = window else {
+            let (adapter, device, queue) =
+                Self::create_adapter_device_and_queue(&self.config, &self.instance, None)?;
+            self.replace_device(adapter, device, queue);
+            return Ok(());
+        };
+        let instance = Self::create_instance(&self.config);
+        let surface = unsafe {
+            instance
+                .create_surface(window)
+                .map_err(|e| GlassError::SurfaceError {
+                    window_id: window.id(),
+                    source: e,
+                })?
+        };
+        let (adapter, device, queue) =
+            Self::create_adapter_device_and_queue(&self.config, &instance, Some(&surface))?;
+        self.replace_device(adapter, device, queue);
+        self.instance = instance;
         Ok(())
     }
 
+    /// List all adapters of the backends with their info, features & limits
+    pub fn enumerate_adapters(backends: Backends) -> Vec<AdapterDescription> {
+        let instance = Instance::new(InstanceDescriptor {
+            backends,
+            ..Default::default()
+        });
+        instance
+            .enumerate_adapters(backends)
+            .enumerate()
+            .map(|(index, adapter)| AdapterDescription {
+                index,
+                info: adapter.get_info(),
+                features: adapter.features(),
+                limits: adapter.limits(),
+            })
+            .collect()
+    }
+
+    fn create_instance(config: &DeviceConfig) -> Instance {
+        Instance::new(InstanceDescriptor {
+            backends: config.backends,
+            ..Default::default()
+        })
+    }
+
     fn create_adapter_device_and_queue(
         config: &DeviceConfig,
         instance: &Instance,
         surface: Option<&Surface>,
     ) -> Result<(Adapter, Device, Queue), GlassError> {
-        let adapter = match wait_async(instance.request_adapter(&RequestAdapterOptions {
-            power_preference: config.power_preference,
-            force_fallback_adapter: false,
-            compatible_surface: surface,
-        })) {
+        let selector = AdapterSelector::from_env().or_else(|| config.adapter.clone());
+        let adapter = match &selector {
+            None | Some(AdapterSelector::Fallback) => {
+                wait_async(instance.request_adapter(&RequestAdapterOptions {
+                    power_preference: config.power_preference,
+                    force_fallback_adapter: selector.is_some(),
+                    compatible_surface: surface,
+                }))
+            }
+            Some(selector) => instance
+                .enumerate_adapters(config.backends)
+                .enumerate()
+                .find(|(index, adapter)| {
+                    selector.matches(*index, &adapter.get_info())
+                        && surface.is_none_or(|s| adapter.is_surface_supported(s))
+                })
+                .map(|(_, adapter)| adapter),
+        };
+        let adapter = match adapter {
             Some(a) => a,
-            None => return Err(GlassError::AdapterError),
+            None => {
+                return Err(GlassError::AdapterError {
+                    backends: config.backends,
+                    power_preference: config.power_preference,
+                    selector,
+                })
+            }
         };
 
+        // Request optional features & limits the adapter supports
+        let features = negotiate_features(
+            config.features,
+            config.optional_features,
+            adapter.features(),
+            adapter.get_info().backend,
+        );
+        let limits = negotiate_limits(&config.limits, &config.optional_limits, &adapter.limits());
+
         let trace_env = std::env::var("WGPU_TRACE").ok();
         let path = trace_env.as_ref().map(std::path::Path::new);
         // Create the logical device and command queue
         let (device, queue) = match wait_async(adapter.request_device(
             &DeviceDescriptor {
                 label: None,
-                features: config.features,
-                limits: config.limits.clone(),
+                features,
+                limits,
             },
             if cfg!(feature = "trace") { path } else { None },
         )) {
             Ok(dq) => dq,
-            Err(e) => return Err(GlassError::DeviceError(e)),
+            Err(e) => {
+                return Err(GlassError::DeviceError {
+                    adapter: adapter.get_info().name,
+                    source: e,
+                })
+            }
         };
 
         Ok((adapter, device, queue))
@@ -150,4 +450,498 @@ impl DeviceContext {
     pub fn queue(&self) -> &Queue {
         &self.queue
     }
+
+    /// Submit command buffers, e.g. a finished encoder, to the queue. Constants written by
+    /// built-in pipelines are uploaded first, see [`upload_constants`]. Returns
+    /// [`GlassError::DeviceLost`] if the device has been lost.
+    pub fn submit<I: IntoIterator<Item = CommandBuffer>>(
+        &self,
+        command_buffers: I,
+    ) -> Result<SubmissionIndex, GlassError> {
+        self.catch_device_loss(|| {
+            upload_constants(&self.device, &self.queue);
+            self.queue.submit(command_buffers)
+        })
+    }
+
+    /// Block until all submitted work has finished. Returns [`GlassError::DeviceLost`] if the
+    /// device has been lost.
+    pub fn wait(&self) -> Result<(), GlassError> {
+        self.catch_device_loss(|| {
+            self.device.poll(Maintain::Wait);
+        })
+    }
+
+    /// Block until the work of `submission` has finished. Returns [`GlassError::DeviceLost`] if
+    /// the device has been lost.
+    pub fn wait_for(&self, submission: SubmissionIndex) -> Result<(), GlassError> {
+        self.catch_device_loss(|| {
+            self.device
+                .poll(Maintain::WaitForSubmissionIndex(submission));
+        })
+    }
+
+    /// Run `f` unless the device has been lost. wgpu 0.16 has no device lost callback, and
+    /// submitting to or polling a lost device panics. If `f` panics and the device no longer
+    /// responds to a poll either, it's flagged lost instead. Other panics are resumed.
+    fn catch_device_loss<R>(&self, f: impl FnOnce() -> R) -> Result<R, GlassError> {
+        if self.is_lost() {
+            return Err(GlassError::DeviceLost);
+        }
+        match panic::catch_unwind(AssertUnwindSafe(f)) {
+            Ok(result) => Ok(result),
+            Err(payload) => {
+                let responds =
+                    panic::catch_unwind(AssertUnwindSafe(|| self.device.poll(Maintain::Poll)))
+                        .is_ok();
+                if responds {
+                    panic::resume_unwind(payload);
+                }
+                self.lost.store(true, Ordering::Release);
+                Err(GlassError::DeviceLost)
+            }
+        }
+    }
+
+    /// Copy the contents of `buffer` back to the cpu. Buffer must have [`BufferUsages::COPY_SRC`].
+    /// Waits for the gpu to finish.
+    pub fn read_buffer(&self, buffer: &Buffer) -> Result<Vec<u8>, GlassError> {
+        let size = buffer.size();
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Readback Commands"),
+            });
+        encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, size);
+        let submission = self.submit(Some(encoder.finish()))?;
+
+        let slice = staging.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.wait_for(submission)?;
+        receiver
+            .recv()
+            .expect("Readback channel closed")
+            .map_err(|e| GlassError::BufferReadError {
+                size,
+                source: e,
+            })?;
+        let data = slice.get_mapped_range().to_vec();
+        staging.unmap();
+        Ok(data)
+    }
+
+    /// Like [`DeviceContext::read_buffer`], but cast to `T`. Buffer size must be a multiple of
+    /// the size of `T`.
+    pub fn read_buffer_as<T: Pod>(&self, buffer: &Buffer) -> Result<Vec<T>, GlassError> {
+        Ok(bytemuck::pod_collect_to_vec(&self.read_buffer(buffer)?))
+    }
+
+    /// Features granted to the device, required & supported optional features
+    pub fn features(&self) -> wgpu::Features {
+        self.device.features()
+    }
+
+    /// Limits granted to the device
+    pub fn limits(&self) -> Limits {
+        self.device.limits()
+    }
+}
+
+/// Required features & the optional features supported on `backend`
+fn negotiate_features(
+    required: wgpu::Features,
+    optional: wgpu::Features,
+    mut supported: wgpu::Features,
+    backend: Backend,
+) -> wgpu::Features {
+    // Gl emulates push constants with plain uniforms, which fail on integer members. Built-in
+    // pipelines fall back to uniform buffers instead, unless push constants are required.
+    if backend == Backend::Gl {
+        supported.remove(wgpu::Features::PUSH_CONSTANTS);
+    }
+    required | (optional & supported)
+}
+
+/// Required limits, raised towards optional limits as far as supported. Offset alignments are
+/// better when lower, other limits when higher.
+fn negotiate_limits(required: &Limits, optional: &Limits, supported: &Limits) -> Limits {
+    let mut limits = required.clone();
+    macro_rules! raise {
+        ($($name:ident),*) => {
+            $(limits.$name = required.$name.max(optional.$name.min(supported.$name));)*
+        };
+    }
+    macro_rules! lower {
+        ($($name:ident),*) => {
+            $(limits.$name = required.$name.min(optional.$name.max(supported.$name));)*
+        };
+    }
+    raise!(
+        max_texture_dimension_1d,
+        max_texture_dimension_2d,
+        max_texture_dimension_3d,
+        max_texture_array_layers,
+        max_bind_groups,
+        max_bindings_per_bind_group,
+        max_dynamic_uniform_buffers_per_pipeline_layout,
+        max_dynamic_storage_buffers_per_pipeline_layout,
+        max_sampled_textures_per_shader_stage,
+        max_samplers_per_shader_stage,
+        max_storage_buffers_per_shader_stage,
+        max_storage_textures_per_shader_stage,
+        max_uniform_buffers_per_shader_stage,
+        max_uniform_buffer_binding_size,
+        max_storage_buffer_binding_size,
+        max_vertex_buffers,
+        max_buffer_size,
+        max_vertex_attributes,
+        max_vertex_buffer_array_stride,
+        max_push_constant_size,
+        max_inter_stage_shader_components,
+        max_compute_workgroup_storage_size,
+        max_compute_invocations_per_workgroup,
+        max_compute_workgroup_size_x,
+        max_compute_workgroup_size_y,
+        max_compute_workgroup_size_z,
+        max_compute_workgroups_per_dimension
+    );
+    lower!(
+        min_uniform_buffer_offset_alignment,
+        min_storage_buffer_offset_alignment
+    );
+    limits
+}
+
+/// Handle errors of the device not captured in error scopes. Device loss is flagged, other
+/// errors are handled according to the error mode. Returns the device lost flag.
+fn set_error_handler(
+    device: &Device,
+    mode: GpuErrorMode,
+    errors: &Arc<Mutex<Vec<GlassError>>>,
+) -> Arc<AtomicBool> {
+    let lost = Arc::new(AtomicBool::new(false));
+    let device_lost = lost.clone();
+    let errors = errors.clone();
+    device.on_uncaptured_error(Box::new(move |error| {
+        if is_device_lost_error(&error) {
+            device_lost.store(true, Ordering::Release);
+        } else {
+            handle_error(mode, &errors, GlassError::GpuError {
+                stage: None,
+                window_id: None,
+                message: error.to_string(),
+            });
+        }
+    }));
+    lost
+}
+
+fn handle_error(mode: GpuErrorMode, errors: &Mutex<Vec<GlassError>>, error: GlassError) {
+    match mode {
+        GpuErrorMode::Panic => panic!("{}", error),
+        GpuErrorMode::Log => eprintln!("{}", error),
+        GpuErrorMode::Collect => errors.lock().unwrap().push(error),
+    }
+}
+
+/// Whether the error or any of its sources is wgpu-core's [`DeviceError::Lost`]
+fn is_device_lost_error(error: &wgpu::Error) -> bool {
+    let mut source: Option<&(dyn Error + 'static)> = Some(error);
+    while let Some(e) = source {
+        if matches!(device_error(e), Some(DeviceError::Lost)) {
+            return true;
+        }
+        source = e.source();
+    }
+    false
+}
+
+/// [`DeviceError`] of `error`. Errors wrapping it transparently skip it in their sources, so
+/// wgpu-core's errors of device & queue operations are unwrapped by type. Check these when
+/// updating wgpu.
+fn device_error<'a>(error: &'a (dyn Error + 'static)) -> Option<&'a DeviceError> {
+    macro_rules! unwrap_device_error {
+        ($($error:ident::$variant:ident),*) => {
+            $(
+                if let Some($error::$variant(device_error)) = error.downcast_ref::<$error>() {
+                    return Some(device_error);
+                }
+            )*
+        };
+    }
+    unwrap_device_error!(
+        CreateBufferError::Device,
+        CreateTextureError::Device,
+        CreateSamplerError::Device,
+        CreateQuerySetError::Device,
+        BufferAccessError::Device,
+        CreateBindGroupLayoutError::Device,
+        CreateBindGroupError::Device,
+        CreatePipelineLayoutError::Device,
+        CreateShaderModuleError::Device,
+        CreateComputePipelineError::Device,
+        CreateRenderPipelineError::Device,
+        ConfigureSurfaceError::Device,
+        SurfaceError::Device,
+        QueueWriteError::Queue,
+        QueueSubmitError::Queue
+    );
+    error.downcast_ref::<DeviceError>()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        panic::{self, AssertUnwindSafe},
+        sync::{atomic::Ordering, Mutex},
+    };
+
+    use wgpu::{
+        AdapterInfo, Backend, BufferDescriptor, BufferUsages, DeviceType, Features, Limits,
+    };
+    use wgpu_core::{
+        device::{queue::QueueSubmitError, DeviceError},
+        error::ContextError,
+        resource::CreateBufferError,
+    };
+
+    use crate::{
+        device_context::{
+            handle_error, is_device_lost_error, negotiate_features, negotiate_limits,
+            AdapterSelector, DeviceConfig, DeviceContext, GpuErrorMode,
+        },
+        GlassApp, GlassConfig, GlassContext, GlassError, GlassStage, HeadlessGlass,
+    };
+
+    /// Error as wgpu reports it from wgpu-core
+    fn wgpu_error(cause: impl std::error::Error + Send + Sync + 'static) -> wgpu::Error {
+        wgpu::Error::Validation {
+            source: Box::new(ContextError {
+                string: "Device::create_buffer",
+                cause: Box::new(cause),
+                label_key: "label",
+                label: String::new(),
+            }),
+            description: "Validation Error".to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_device_lost_error() {
+        assert!(is_device_lost_error(&wgpu_error(DeviceError::Lost)));
+        assert!(is_device_lost_error(&wgpu_error(
+            CreateBufferError::Device(DeviceError::Lost)
+        )));
+        assert!(is_device_lost_error(&wgpu_error(QueueSubmitError::Queue(
+            DeviceError::Lost
+        ))));
+        assert!(!is_device_lost_error(&wgpu_error(DeviceError::OutOfMemory)));
+        assert!(!is_device_lost_error(&wgpu_error(
+            CreateBufferError::UnalignedSize
+        )));
+    }
+
+    #[test]
+    fn test_error_scope_survives_device_replacement() {
+        let config = DeviceConfig {
+            error_mode: GpuErrorMode::Collect,
+            ..DeviceConfig::default()
+        };
+        // Needs an adapter
+        let Ok(mut context) = DeviceContext::new_headless(&config) else {
+            return;
+        };
+        context.push_error_scope();
+        // Mappable buffers can only be copied to
+        context.device().create_buffer(&BufferDescriptor {
+            label: None,
+            size: 4,
+            usage: BufferUsages::MAP_READ | BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        // Device is replaced within the scope, like by reconfigure_with_surface when the first
+        // window is created in a stage
+        let (adapter, device, queue) =
+            DeviceContext::create_adapter_device_and_queue(&config, &context.instance, None)
+                .unwrap();
+        context.replace_device(adapter, device, queue);
+        context.pop_error_scope(GlassStage::Start, None);
+        let errors = context.take_errors();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], GlassError::GpuError {
+            stage: Some(GlassStage::Start),
+            ..
+        }));
+        // Unbalanced pops are ignored
+        context.pop_error_scope(GlassStage::Start, None);
+    }
+
+    #[derive(Default)]
+    struct RecreateApp {
+        recreated: usize,
+    }
+
+    impl GlassApp for RecreateApp {
+        fn device_recreated(&mut self, _context: &mut GlassContext) {
+            self.recreated += 1;
+        }
+    }
+
+    #[test]
+    fn test_lost_device_is_recreated() {
+        // Needs an adapter
+        let Ok(mut glass) =
+            HeadlessGlass::new(RecreateApp::default(), GlassConfig::performance(4, 4))
+        else {
+            return;
+        };
+        glass.step().unwrap();
+        let old_device = glass.context().device().global_id();
+        glass
+            .context()
+            .device_context()
+            .lost
+            .store(true, Ordering::Release);
+        assert!(matches!(
+            glass.context().submit(None),
+            Err(GlassError::DeviceLost)
+        ));
+        glass.step().unwrap();
+        assert_eq!(glass.app().recreated, 1);
+        assert!(!glass.context().is_device_lost());
+        assert_ne!(glass.context().device().global_id(), old_device);
+        // Offscreen targets were recreated on the new device
+        glass.read_target(0).unwrap();
+    }
+
+    #[test]
+    fn test_panics_of_responsive_device_are_resumed() {
+        // Needs an adapter
+        let Ok(context) = DeviceContext::new_headless(&DeviceConfig::default()) else {
+            return;
+        };
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            context.catch_device_loss(|| panic!("Not a device loss"))
+        }));
+        assert!(result.is_err());
+        assert!(!context.is_lost());
+    }
+
+    #[test]
+    fn test_collect_errors() {
+        let errors = Mutex::new(vec![]);
+        handle_error(GpuErrorMode::Collect, &errors, GlassError::GpuError {
+            stage: Some(GlassStage::Render),
+            window_id: None,
+            message: "Buffer is too small".to_owned(),
+        });
+        let errors = errors.into_inner().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "wgpu error in stage render: Buffer is too small"
+        );
+    }
+
+    #[test]
+    fn test_adapter_selector() {
+        let parse = |s: &str| s.parse::<AdapterSelector>().unwrap();
+        assert_eq!(parse("1"), AdapterSelector::Index(1));
+        assert_eq!(parse("0x10de:2204"), AdapterSelector::Id {
+            vendor: 0x10de,
+            device: 0x2204,
+        });
+        assert_eq!(
+            parse("Discrete"),
+            AdapterSelector::DeviceType(DeviceType::DiscreteGpu)
+        );
+        assert_eq!(parse("fallback"), AdapterSelector::Fallback);
+        assert_eq!(
+            parse("llvmpipe"),
+            AdapterSelector::Name("llvmpipe".to_owned())
+        );
+
+        let info = AdapterInfo {
+            name: "llvmpipe (LLVM 15.0.7, 256 bits)".to_owned(),
+            vendor: 0x10005,
+            device: 0,
+            device_type: DeviceType::Cpu,
+            driver: String::new(),
+            driver_info: String::new(),
+            backend: Backend::Vulkan,
+        };
+        assert!(parse("LLVMpipe").matches(0, &info));
+        assert!(parse("cpu").matches(0, &info));
+        assert!(parse("0").matches(0, &info));
+        assert!(!parse("1").matches(0, &info));
+        assert!(!parse("nvidia").matches(0, &info));
+    }
+
+    #[test]
+    fn test_negotiate_features() {
+        let optional =
+            Features::PUSH_CONSTANTS | Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+        let supported =
+            Features::PUSH_CONSTANTS | Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+        assert_eq!(
+            negotiate_features(Features::empty(), optional, supported, Backend::Vulkan),
+            optional
+        );
+        // Push constants are not requested on gl, unless required
+        assert_eq!(
+            negotiate_features(Features::empty(), optional, supported, Backend::Gl),
+            Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+        );
+        assert_eq!(
+            negotiate_features(Features::PUSH_CONSTANTS, optional, supported, Backend::Gl),
+            optional
+        );
+        assert_eq!(
+            negotiate_features(
+                Features::empty(),
+                optional,
+                Features::empty(),
+                Backend::Vulkan
+            ),
+            Features::empty()
+        );
+    }
+
+    #[test]
+    fn test_negotiate_limits() {
+        let required = Limits::downlevel_defaults();
+        let optional = Limits {
+            max_push_constant_size: 128,
+            max_texture_dimension_2d: 16384,
+            min_uniform_buffer_offset_alignment: 64,
+            ..Limits::default()
+        };
+        let supported = Limits {
+            max_push_constant_size: 0,
+            max_texture_dimension_2d: 8192,
+            min_uniform_buffer_offset_alignment: 32,
+            ..Limits::default()
+        };
+        let limits = negotiate_limits(&required, &optional, &supported);
+        assert_eq!(limits.max_push_constant_size, 0);
+        assert_eq!(limits.max_texture_dimension_2d, 8192);
+        assert_eq!(limits.min_uniform_buffer_offset_alignment, 64);
+        assert!(limits.check_limits(&supported));
+        // Required limits are kept even if unsupported, so device creation fails
+        let required = Limits {
+            max_push_constant_size: 256,
+            ..required
+        };
+        let limits = negotiate_limits(&required, &optional, &supported);
+        assert_eq!(limits.max_push_constant_size, 256);
+    }
 }
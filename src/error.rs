@@ -0,0 +1,280 @@
+use std::{error::Error, fmt::Formatter, path::PathBuf};
+
+use image::ImageError;
+use wgpu::{
+    Backends, BufferAsyncError, CreateSurfaceError, PowerPreference, RequestDeviceError,
+    TextureFormat,
+};
+use winit::{error::OsError, window::WindowId};
+
+use crate::{device_context::AdapterSelector, window::SurfaceError, GlassStage};
+
+/// Errors of Glass. Variants hold the context in which the error happened, e.g. the file or the
+/// window, and the underlying error is available through [`Error::source`].
+#[derive(Debug)]
+pub enum GlassError {
+    /// Creating a window failed
+    WindowError {
+        title: String,
+        source: OsError,
+    },
+    /// Creating the surface of a window failed
+    SurfaceError {
+        window_id: WindowId,
+        source: CreateSurfaceError,
+    },
+    /// No adapter matched the device config
+    AdapterError {
+        backends: Backends,
+        power_preference: PowerPreference,
+        selector: Option<AdapterSelector>,
+    },
+    /// Requesting a device from the adapter failed
+    DeviceError {
+        adapter: String,
+        source: RequestDeviceError,
+    },
+    /// Decoding or encoding an image failed. Name is the file path or the texture label.
+    ImageError {
+        name: String,
+        source: ImageError,
+    },
+    /// Acquiring the next frame of a window failed and rendering cannot continue
+    SwapchainError {
+        window_id: WindowId,
+        source: SurfaceError,
+    },
+    /// Validation or out of memory error of wgpu. Stage & window are known when the error was
+    /// captured around a stage of the app, see
+    /// [`GpuErrorMode`](crate::device_context::GpuErrorMode).
+    GpuError {
+        stage: Option<GlassStage>,
+        window_id: Option<WindowId>,
+        message: String,
+    },
+    /// The device was lost. [`Glass`](crate::Glass) & [`HeadlessGlass`](crate::HeadlessGlass)
+    /// recreate it at the start of the next frame.
+    DeviceLost,
+    /// Mapping a buffer for reading it back to the cpu failed
+    BufferReadError {
+        size: u64,
+        source: BufferAsyncError,
+    },
+    /// Reading back a texture of this format is not supported
+    TextureReadError {
+        format: TextureFormat,
+    },
+    /// Reading or writing a file failed
+    IoError {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// Shader file does not have the `wgsl` extension
+    ShaderExtensionError {
+        path: PathBuf,
+    },
+    /// Shader includes a file that has already been included
+    ShaderIncludeError {
+        path: PathBuf,
+        include: String,
+    },
+    /// Shader was not found in statically included sources
+    ShaderNotFoundError {
+        path: PathBuf,
+    },
+    /// Parsing wgsl failed. Message contains the parse error and the included files.
+    ShaderParseError {
+        path: PathBuf,
+        message: String,
+    },
+    RonSerializeError {
+        path: PathBuf,
+        source: ron::Error,
+    },
+    RonDeserializeError {
+        path: PathBuf,
+        source: ron::error::SpannedError,
+    },
+    /// Error returned by the app from a fallible stage. Displayed as is.
+    App(Box<dyn Error + Send + Sync>),
+}
+
+impl GlassError {
+    /// Wrap an app defined error, or an error message
+    pub fn app(error: impl Into<Box<dyn Error + Send + Sync>>) -> GlassError {
+        GlassError::App(error.into())
+    }
+}
+
+impl std::fmt::Display for GlassError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GlassError::WindowError {
+                title, ..
+            } => write!(f, "Failed to create window {:?}", title),
+            GlassError::SurfaceError {
+                window_id, ..
+            } => write!(f, "Failed to create surface for window {:?}", window_id),
+            GlassError::AdapterError {
+                backends,
+                power_preference,
+                selector,
+            } => match selector {
+                Some(selector) => write!(
+                    f,
+                    "No adapter matching {:?} found for backends {:?}",
+                    selector, backends
+                ),
+                None => write!(
+                    f,
+                    "No adapter found for backends {:?} with power preference {:?}",
+                    backends, power_preference
+                ),
+            },
+            GlassError::DeviceError {
+                adapter, ..
+            } => write!(f, "Failed to request device from adapter {:?}", adapter),
+            GlassError::ImageError {
+                name, ..
+            } => write!(f, "Failed to process image {:?}", name),
+            GlassError::SwapchainError {
+                window_id, ..
+            } => write!(f, "Failed to acquire frame of window {:?}", window_id),
+            GlassError::GpuError {
+                stage,
+                window_id,
+                message,
+            } => {
+                write!(f, "wgpu error")?;
+                if let Some(stage) = stage {
+                    write!(f, " in stage {}", stage)?;
+                }
+                if let Some(window_id) = window_id {
+                    write!(f, " of window {:?}", window_id)?;
+                }
+                write!(f, ": {}", message)
+            }
+            GlassError::DeviceLost => write!(f, "Device was lost"),
+            GlassError::BufferReadError {
+                size, ..
+            } => write!(f, "Failed to read back buffer of {} bytes", size),
+            GlassError::TextureReadError {
+                format,
+            } => write!(
+                f,
+                "Reading back textures of format {:?} is not supported",
+                format
+            ),
+            GlassError::IoError {
+                path, ..
+            } => write!(f, "Failed to access file {:?}", path),
+            GlassError::ShaderExtensionError {
+                path,
+            } => write!(f, "Shader {:?} must have the wgsl extension", path),
+            GlassError::ShaderIncludeError {
+                path,
+                include,
+            } => write!(
+                f,
+                "Shader {:?} includes {:?}, which has already been included",
+                path, include
+            ),
+            GlassError::ShaderNotFoundError {
+                path,
+            } => write!(f, "Shader {:?} not found in static sources", path),
+            GlassError::ShaderParseError {
+                path,
+                message,
+            } => write!(f, "Failed to parse shader {:?}:\n{}", path, message),
+            GlassError::RonSerializeError {
+                path, ..
+            } => write!(f, "Failed to serialize {:?}", path),
+            GlassError::RonDeserializeError {
+                path, ..
+            } => write!(f, "Failed to deserialize {:?}", path),
+            GlassError::App(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for GlassError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GlassError::WindowError {
+                source, ..
+            } => Some(source),
+            GlassError::SurfaceError {
+                source, ..
+            } => Some(source),
+            GlassError::DeviceError {
+                source, ..
+            } => Some(source),
+            GlassError::ImageError {
+                source, ..
+            } => Some(source),
+            GlassError::SwapchainError {
+                source, ..
+            } => Some(source),
+            GlassError::BufferReadError {
+                source, ..
+            } => Some(source),
+            GlassError::IoError {
+                source, ..
+            } => Some(source),
+            GlassError::RonSerializeError {
+                source, ..
+            } => Some(source),
+            GlassError::RonDeserializeError {
+                source, ..
+            } => Some(source),
+            // App error is displayed as is, so its source is ours
+            GlassError::App(e) => e.source(),
+            GlassError::AdapterError {
+                ..
+            }
+            | GlassError::DeviceLost
+            | GlassError::GpuError {
+                ..
+            }
+            | GlassError::TextureReadError {
+                ..
+            }
+            | GlassError::ShaderExtensionError {
+                ..
+            }
+            | GlassError::ShaderIncludeError {
+                ..
+            }
+            | GlassError::ShaderNotFoundError {
+                ..
+            }
+            | GlassError::ShaderParseError {
+                ..
+            } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::GlassError;
+
+    #[test]
+    fn test_error_source_and_context() {
+        let error = GlassError::IoError {
+            path: "shaders/missing.wgsl".into(),
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "not found"),
+        };
+        assert!(error.to_string().contains("shaders/missing.wgsl"));
+        assert_eq!(error.source().unwrap().to_string(), "not found");
+
+        let error = GlassError::app("app failed");
+        assert_eq!(error.to_string(), "app failed");
+        assert!(error.source().is_none());
+        // Converts into boxed errors, e.g. for anyhow
+        let boxed: Box<dyn Error + Send + Sync> = error.into();
+        assert_eq!(boxed.to_string(), "app failed");
+    }
+}
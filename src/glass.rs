@@ -1,170 +1,405 @@
-use std::fmt::Formatter;
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
-use image::ImageError;
 use indexmap::IndexMap;
 use wgpu::{
-    Adapter, CreateSurfaceError, Device, Instance, PowerPreference, Queue, RequestDeviceError,
-    SurfaceConfiguration,
+    Adapter, CommandBuffer, Device, Instance, PowerPreference, Queue, SubmissionIndex,
+    SurfaceConfiguration, TextureFormat,
 };
 use winit::{
-    error::OsError,
+    dpi::LogicalSize,
     event::{ElementState, Event, VirtualKeyCode, WindowEvent},
-    event_loop::{EventLoop, EventLoopWindowTarget},
+    event_loop::{EventLoop, EventLoopBuilder, EventLoopProxy, EventLoopWindowTarget},
+    platform::run_return::EventLoopExtRunReturn,
     window::{Fullscreen, Window, WindowId},
 };
 
 use crate::{
+    actions::ActionMap,
     device_context::{DeviceConfig, DeviceContext},
+    input::InputState,
+    recording::{InputRecorder, InputRecording, InputReplay, RecordedFrame, RecordedWindowEvent},
+    runner::GlassRunner,
+    time::{FixedTimestep, FrameTimer},
     window::{
-        get_best_videomode, get_centered_window_position, get_fitting_videomode, GlassWindow,
-        WindowConfig, WindowPos,
+        monitor_infos, select_monitor, window_placement, GlassWindow, MonitorInfo, Placement,
+        SurfaceError, WindowConfig, WindowGeometries, WindowGeometry, DEFAULT_SURFACE_FORMATS,
     },
-    GlassApp, RenderData,
+    GlassApp, GlassError, GlassPlugin, GlassStage, RenderTarget,
 };
 
 /// [`Glass`] is an application that exposes an easy to use API to organize your winit applications
 /// which render using wgpu. Just impl [`GlassApp`] for your application (of any type) and you
 /// are good to go.
-pub struct Glass<A> {
-    app: A,
+///
+/// `E` is the type of user events sent through an [`EventLoopProxy`], see
+/// [`GlassContext::event_loop_proxy`].
+pub struct Glass<A, E: 'static = ()> {
+    runner: GlassRunner<A, E>,
     config: GlassConfig,
 }
 
-impl<A: GlassApp + 'static> Glass<A> {
-    pub fn new(app: A, config: GlassConfig) -> Glass<A> {
+impl<A: GlassApp<E> + 'static, E: 'static> Glass<A, E> {
+    pub fn new(app: A, config: GlassConfig) -> Glass<A, E> {
         Glass {
-            app,
+            runner: GlassRunner::new(app),
             config,
         }
     }
 
+    /// Add a plugin, which is run around the app's stages as described in [`GlassPlugin`].
+    pub fn with_plugin(mut self, plugin: impl GlassPlugin<E> + 'static) -> Glass<A, E> {
+        self.runner.add_plugin(Box::new(plugin));
+        self
+    }
+
+    /// Run the app until all windows are closed or [`GlassContext::exit`] is called. Returns an
+    /// error if rendering cannot continue or if a fallible stage of the app returns an error. In
+    /// both cases `end` is run before returning. Window geometries are saved on exit, see
+    /// [`GlassContext::save_window_geometries`].
     pub fn run(mut self) -> Result<(), GlassError> {
-        let event_loop = EventLoop::new();
+        let mut event_loop = EventLoopBuilder::<E>::with_user_event().build();
         let mut context = GlassContext::new(&event_loop, self.config.clone())?;
-        self.app.start(&event_loop, &mut context);
-        let mut remove_windows = vec![];
-        let mut request_window_close = false;
-
-        event_loop.run(move |event, event_loop, control_flow| {
-            control_flow.set_poll();
-
-            // Run input fn
-            self.app.input(&mut context, event_loop, &event);
-            match event {
-                Event::WindowEvent {
-                    window_id,
-                    event: window_event,
-                    ..
-                } => {
-                    if let Some(window) = context.windows.get_mut(&window_id) {
-                        match window_event {
-                            WindowEvent::Resized(physical_size) => {
-                                // On windows, minimized app can have 0,0 size
-                                if physical_size.width > 0 && physical_size.height > 0 {
-                                    window.configure_surface_with_size(
-                                        context.device_context.device(),
-                                        physical_size,
-                                    );
-                                }
+        if let Err(e) = self.runner.start(Some(&event_loop), &mut context) {
+            // The app's error takes precedence over failing to save
+            let _ = context.save_window_geometries();
+            self.runner.end(&mut context);
+            return Err(e);
+        }
+        let mut result = Ok(());
+        let mut ended = false;
+        let mut last_frame = Instant::now();
+
+        event_loop.run_return(|event, event_loop, control_flow| {
+            // Events may still arrive after exit
+            if ended {
+                return;
+            }
+            match context.control_flow_policy {
+                ControlFlowPolicy::Poll => control_flow.set_poll(),
+                ControlFlowPolicy::Wait => control_flow.set_wait(),
+                ControlFlowPolicy::WaitUntil(fps) => {
+                    control_flow.set_wait_until(last_frame + Duration::from_secs_f64(1.0 / fps))
+                }
+                // Don't sleep while some window is waiting to be redrawn
+                ControlFlowPolicy::Reactive => {
+                    if context.windows.values().any(|w| w.needs_redraw()) {
+                        control_flow.set_poll();
+                    } else {
+                        control_flow.set_wait();
+                    }
+                }
+            }
+
+            // Live input & focus changes are ignored while replaying a recording
+            if let Event::WindowEvent {
+                event: window_event,
+                ..
+            } = &event
+            {
+                if context.is_replaying()
+                    && RecordedWindowEvent::from_window_event(window_event)
+                        .is_some_and(|e| e.is_replaced_by_replay())
+                {
+                    return;
+                }
+            }
+
+            let event_result = self
+                .handle_event(&mut context, event_loop, &event, false)
+                .and_then(|_| match event {
+                    Event::MainEventsCleared => {
+                        self.run_frame(&mut context, event_loop, &mut last_frame)
+                    }
+                    Event::UserEvent(user_event) => self
+                        .runner
+                        .user_event(&mut context, user_event)
+                        .map(|_| false),
+                    _ => Ok(false),
+                });
+            let exit = event_result.unwrap_or_else(|e| {
+                result = Err(e);
+                true
+            });
+            if exit {
+                control_flow.set_exit();
+                let saved = context.save_window_geometries();
+                if result.is_ok() {
+                    result = saved;
+                }
+                // Run end
+                self.runner.end(&mut context);
+                ended = true;
+            }
+        });
+        result
+    }
+
+    /// Run a frame: fixed update, update, window closing & rendering. Returns whether the app
+    /// exited, either by request or because all windows have been closed.
+    fn run_frame(
+        &mut self,
+        context: &mut GlassContext,
+        event_loop: &EventLoopWindowTarget<E>,
+        last_frame: &mut Instant,
+    ) -> Result<bool, GlassError> {
+        let now = Instant::now();
+        if let ControlFlowPolicy::WaitUntil(fps) = context.control_flow_policy {
+            // Woken up early by events, wait until next frame is due
+            if now < *last_frame + Duration::from_secs_f64(1.0 / fps) {
+                return Ok(false);
+            }
+        }
+        let mut delta = now - *last_frame;
+        *last_frame = now;
+        // Rebuild device & surfaces if the device was lost
+        if context.is_device_lost() {
+            context.recreate_device()?;
+            self.runner.device_recreated(context)?;
+        }
+        // Replay recorded input with recorded frame time
+        if let Some(frame) = context.next_replay_frame() {
+            delta = frame.delta;
+            for recorded in frame.events {
+                let Some((&mut window_id, window)) = context.windows.get_index_mut(recorded.window)
+                else {
+                    continue;
+                };
+                match recorded.event.to_window_event() {
+                    Some(event) => {
+                        let event = Event::WindowEvent {
+                            window_id,
+                            event,
+                        };
+                        self.handle_event(context, event_loop, &event, true)?;
+                    }
+                    // Scale factor only applies to input, the live window keeps its size
+                    None => window.input_mut().process_recorded_event(&recorded.event),
+                }
+            }
+        }
+        if let Some(recorder) = &mut context.recorder {
+            recorder.record_frame(delta);
+        }
+        context.frame_timer.begin_frame(delta);
+        context.update_actions();
+        self.runner.fixed_update(context)?;
+        self.runner.update(context)?;
+        // Close window(s)
+        if context.exit {
+            context.pending_window_closes = context.windows.keys().copied().collect();
+        }
+        if !context.pending_window_closes.is_empty() {
+            for window_id in std::mem::take(&mut context.pending_window_closes) {
+                if context.windows.contains_key(&window_id) {
+                    self.runner.window_closed(context, window_id);
+                    // Saved on exit
+                    context.store_window_geometry(window_id);
+                    context.windows.remove(&window_id);
+                }
+            }
+            // Exit once the last window is closed
+            if context.windows.is_empty() {
+                return Ok(true);
+            }
+        }
+        // Exit on request, also without windows
+        if context.exit {
+            return Ok(true);
+        }
+        // Render
+        let reactive = context.control_flow_policy == ControlFlowPolicy::Reactive;
+        let window_ids = context
+            .windows
+            .iter()
+            .filter(|(_, w)| !reactive || w.needs_redraw())
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for window_id in window_ids {
+            let window = context.windows.get_mut(&window_id).unwrap();
+            let frame = window.acquire_frame(context.device_context.device());
+            match frame {
+                Ok(frame) => {
+                    let window = &context.windows[&window_id];
+                    let mut encoder = context.device_context.device().create_command_encoder(
+                        &wgpu::CommandEncoderDescriptor {
+                            label: Some("Render Commands"),
+                        },
+                    );
+
+                    // Run render & post processing functions
+                    let target = RenderTarget::Window(window);
+                    let render_time =
+                        self.runner
+                            .render(context, &mut encoder, target, &frame.texture)?;
+                    let post_processing_time = self.runner.post_processing(
+                        context,
+                        &mut encoder,
+                        target,
+                        &frame.texture,
+                    )?;
+
+                    // Device is recreated next frame
+                    if context.submit(Some(encoder.finish())).is_err() {
+                        // Presenting or discarding a frame of a lost device panics
+                        std::mem::forget(frame);
+                        context.windows[&window_id].set_needs_redraw(true);
+                        break;
+                    }
+                    frame.present();
+                    context.windows[&window_id].set_needs_redraw(false);
+
+                    let after_render_time = self.runner.after_render(context)?;
+
+                    let timer = &mut context.frame_timer;
+                    timer.add_stage_time(GlassStage::Render, render_time);
+                    timer.add_stage_time(GlassStage::PostProcessing, post_processing_time);
+                    timer.add_stage_time(GlassStage::AfterRender, after_render_time);
+                }
+                Err(SurfaceError::Timeout) => {
+                    // Try again next frame
+                    context.windows[&window_id].set_needs_redraw(true);
+                    self.runner.surface_timeout(context, window_id);
+                }
+                // Surface could not be reconfigured (e.g. minimized window), skip frame
+                Err(SurfaceError::Outdated | SurfaceError::Lost) => {
+                    context.windows[&window_id].set_needs_redraw(false);
+                }
+                // Rendering cannot continue
+                Err(error) => {
+                    return Err(GlassError::SwapchainError {
+                        window_id,
+                        source: error,
+                    })
+                }
+            }
+            if context.control_flow_policy == ControlFlowPolicy::Poll {
+                context.windows[&window_id].request_redraw();
+            }
+        }
+        // End of frame
+        self.runner.end_of_frame(context)?;
+        for window in context.windows.values_mut() {
+            window.input_mut().end_frame();
+        }
+        context.frame_timer.end_frame();
+        Ok(false)
+    }
+
+    /// Update input state & windows from an event and run input stage. While replaying, only
+    /// `replayed` events update input state.
+    fn handle_event(
+        &mut self,
+        context: &mut GlassContext,
+        event_loop: &EventLoopWindowTarget<E>,
+        event: &Event<E>,
+        replayed: bool,
+    ) -> Result<(), GlassError> {
+        // Update input state before app sees the event
+        if let Event::WindowEvent {
+            window_id,
+            event: window_event,
+        } = event
+        {
+            let index = context.windows.get_index_of(window_id);
+            if let Some(index) = index.filter(|_| replayed || !context.is_replaying()) {
+                if let Some(recorder) = &mut context.recorder {
+                    recorder.record_event(index, window_event);
+                }
+                context.windows[index]
+                    .input_mut()
+                    .process_event(window_event);
+            }
+        }
+
+        // Run input fn
+        self.runner.input(context, event_loop, event)?;
+        match event {
+            Event::WindowEvent {
+                window_id,
+                event: window_event,
+            } => {
+                let window_id = *window_id;
+                let mut resized = None;
+                if let Some(window) = context.windows.get_mut(&window_id) {
+                    // Input & window changes redraw the window in reactive mode
+                    window.set_needs_redraw(true);
+                    match window_event {
+                        WindowEvent::Resized(physical_size) => {
+                            // On windows, minimized app can have 0,0 size
+                            if physical_size.width > 0 && physical_size.height > 0 {
+                                window.configure_surface_with_size(
+                                    context.device_context.device(),
+                                    *physical_size,
+                                );
                             }
-                            WindowEvent::ScaleFactorChanged {
-                                new_inner_size, ..
-                            } => {
+                            window.update_normal_geometry(*physical_size);
+                            resized = Some((*physical_size, window.window().scale_factor()));
+                        }
+                        WindowEvent::ScaleFactorChanged {
+                            scale_factor,
+                            new_inner_size,
+                        } => {
+                            if new_inner_size.width > 0 && new_inner_size.height > 0 {
                                 window.configure_surface_with_size(
                                     context.device_context.device(),
-                                    *new_inner_size,
+                                    **new_inner_size,
                                 );
                             }
-                            WindowEvent::KeyboardInput {
-                                input,
-                                is_synthetic,
-                                ..
-                            } => {
-                                if let Some(key) = input.virtual_keycode {
-                                    if !is_synthetic
-                                        && window.exit_on_esc()
-                                        && window.is_focused()
-                                        && key == VirtualKeyCode::Escape
-                                        && input.state == ElementState::Pressed
-                                    {
-                                        request_window_close = true;
-                                        remove_windows.push(window_id);
-                                    }
+                            // The window is resized to this after the event
+                            window.update_normal_geometry(**new_inner_size);
+                            resized = Some((**new_inner_size, *scale_factor));
+                        }
+                        WindowEvent::KeyboardInput {
+                            input,
+                            is_synthetic,
+                            ..
+                        } => {
+                            if let Some(key) = input.virtual_keycode {
+                                if !is_synthetic
+                                    && window.exit_on_esc()
+                                    && window.is_focused()
+                                    && key == VirtualKeyCode::Escape
+                                    && input.state == ElementState::Pressed
+                                    && self.runner.close_requested(context, window_id)
+                                {
+                                    context.close_window(window_id);
                                 }
                             }
-                            WindowEvent::Focused(has_focus) => {
-                                window.set_focus(has_focus);
-                            }
-                            WindowEvent::CloseRequested => {
-                                request_window_close = true;
-                                remove_windows.push(window_id);
-                            }
-                            _ => (),
                         }
-                    }
-                }
-                Event::MainEventsCleared => {
-                    self.app.update(&mut context);
-                    // Close window(s)
-                    if request_window_close || context.exit {
-                        for window in remove_windows.iter() {
-                            context.windows.remove(window);
+                        WindowEvent::Moved(_) => {
+                            let inner_size = window.window().inner_size();
+                            window.update_normal_geometry(inner_size);
                         }
-                        remove_windows.clear();
-                        request_window_close = false;
-                        // Exit
-                        if context.windows.is_empty() || context.exit {
-                            control_flow.set_exit();
-                            // Run end
-                            self.app.end(&mut context);
+                        WindowEvent::Focused(has_focus) => {
+                            window.set_focus(*has_focus);
                         }
-                    }
-                    // Render
-                    for (_, window) in context.windows.iter() {
-                        match window.surface().get_current_texture() {
-                            Ok(frame) => {
-                                let mut encoder = context
-                                    .device_context
-                                    .device()
-                                    .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                                        label: Some("Render Commands"),
-                                    });
-
-                                // Run render & post processing functions
-                                self.app.render(&context, RenderData {
-                                    encoder: &mut encoder,
-                                    window,
-                                    frame: &frame,
-                                });
-                                self.app.post_processing(&context, RenderData {
-                                    encoder: &mut encoder,
-                                    window,
-                                    frame: &frame,
-                                });
-
-                                context
-                                    .device_context
-                                    .queue()
-                                    .submit(Some(encoder.finish()));
-
-                                frame.present();
-
-                                self.app.after_render(&context);
-                            }
-                            Err(error) => {
-                                if error == wgpu::SurfaceError::OutOfMemory {
-                                    panic!("Swapchain error: {error}. Rendering cannot continue.")
-                                }
-                            }
+                        WindowEvent::CloseRequested
+                            if self.runner.close_requested(context, window_id) =>
+                        {
+                            context.close_window(window_id);
                         }
-                        window.window().request_redraw();
+                        _ => (),
                     }
-                    // End of frame
-                    self.app.end_of_frame(&mut context);
                 }
-                _ => {}
+                // Surface has been reconfigured, let app resize its targets
+                if let Some((new_size, scale_factor)) = resized {
+                    self.runner
+                        .resized(context, window_id, new_size, scale_factor)?;
+                }
             }
-        });
+            Event::RedrawRequested(window_id) => {
+                if let Some(window) = context.windows.get_mut(window_id) {
+                    window.redraw_requested();
+                }
+            }
+            _ => {}
+        }
+        Ok(())
     }
 }
 
@@ -173,6 +408,39 @@ impl<A: GlassApp + 'static> Glass<A> {
 pub struct GlassConfig {
     pub device_config: DeviceConfig,
     pub window_configs: Vec<WindowConfig>,
+    /// Fixed updates per second. When set, [`GlassApp::fixed_update`] is run zero or more times
+    /// each frame before [`GlassApp::update`] to keep up with this rate.
+    pub fixed_update_rate: Option<f64>,
+    /// How the event loop runs frames. Can be changed at runtime with
+    /// [`GlassContext::set_control_flow_policy`].
+    pub control_flow_policy: ControlFlowPolicy,
+    /// Opt-in file to save window geometries to, for windows with a
+    /// [`WindowConfig::persistence_id`]. Loaded at start, saved when the app exits.
+    pub window_geometry_file: Option<PathBuf>,
+}
+
+/// Determines when frames are run & windows are rendered.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ControlFlowPolicy {
+    /// Run frames continuously as fast as possible.
+    Poll,
+    /// Run a frame only when events arrive.
+    Wait,
+    /// Run frames at most at the given frames per second, which must be positive.
+    WaitUntil(f64),
+    /// Like [`ControlFlowPolicy::Wait`], but windows are only rendered when they need a redraw.
+    /// A window needs a redraw after input or resize events, or when requested with
+    /// [`GlassContext::request_redraw`].
+    Reactive,
+}
+
+impl ControlFlowPolicy {
+    fn validated(self) -> ControlFlowPolicy {
+        if let ControlFlowPolicy::WaitUntil(fps) = self {
+            assert!(fps > 0.0, "Frames per second of WaitUntil must be positive");
+        }
+        self
+    }
 }
 
 impl GlassConfig {
@@ -180,6 +448,9 @@ impl GlassConfig {
         Self {
             device_config: DeviceConfig::default(),
             window_configs: vec![],
+            fixed_update_rate: None,
+            control_flow_policy: ControlFlowPolicy::Poll,
+            window_geometry_file: None,
         }
     }
 
@@ -195,6 +466,9 @@ impl GlassConfig {
                 exit_on_esc: false,
                 ..WindowConfig::default()
             }],
+            fixed_update_rate: None,
+            control_flow_policy: ControlFlowPolicy::Poll,
+            window_geometry_file: None,
         }
     }
 }
@@ -204,66 +478,77 @@ impl Default for GlassConfig {
         Self {
             device_config: DeviceConfig::default(),
             window_configs: vec![WindowConfig::default()],
+            fixed_update_rate: None,
+            control_flow_policy: ControlFlowPolicy::Poll,
+            window_geometry_file: None,
         }
     }
 }
 
-#[derive(Debug)]
-pub enum GlassError {
-    WindowError(OsError),
-    SurfaceError(CreateSurfaceError),
-    AdapterError,
-    DeviceError(RequestDeviceError),
-    ImageError(ImageError),
-}
-
-impl std::fmt::Display for GlassError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let s = match self {
-            GlassError::WindowError(e) => format!("WindowError: {}", e),
-            GlassError::SurfaceError(e) => format!("SurfaceError: {}", e),
-            GlassError::AdapterError => "AdapterError".to_owned(),
-            GlassError::DeviceError(e) => format!("DeviceError: {}", e),
-            GlassError::ImageError(e) => format!("ImageError: {}", e),
-        };
-        write!(f, "{}", s)
-    }
-}
-
 /// The runtime context accessible through [`GlassApp`].
 /// You can use the context to create windows at runtime. Or access devices, which are often
 /// needed for render or compute functionality.
 pub struct GlassContext {
     device_context: DeviceContext,
     windows: IndexMap<WindowId, GlassWindow>,
+    fixed_timestep: Option<FixedTimestep>,
+    frame_timer: FrameTimer,
+    // Proxy of the event loop & name of its user event type
+    event_loop_proxy: Option<(Box<dyn Any>, &'static str)>,
+    control_flow_policy: ControlFlowPolicy,
+    created_windows: Vec<WindowId>,
+    pending_window_closes: Vec<WindowId>,
+    states: HashMap<TypeId, Box<dyn Any>>,
+    actions: ActionMap,
+    offscreen_inputs: Vec<InputState>,
+    offscreen_formats: Vec<TextureFormat>,
+    window_geometry_file: Option<PathBuf>,
+    window_geometries: WindowGeometries,
+    recorder: Option<InputRecorder>,
+    replay: Option<InputReplay>,
     exit: bool,
 }
 
 impl GlassContext {
-    pub fn new(event_loop: &EventLoop<()>, mut config: GlassConfig) -> Result<Self, GlassError> {
+    pub fn new<E: 'static>(
+        event_loop: &EventLoop<E>,
+        config: GlassConfig,
+    ) -> Result<Self, GlassError> {
+        let window_geometries = load_window_geometries(config.window_geometry_file.as_deref());
         // Create windows from initial configs
         let mut winit_windows = vec![];
-        for &window_config in config.window_configs.iter() {
+        for window_config in config.window_configs.iter() {
+            let geometry = window_config
+                .persistence_id
+                .as_ref()
+                .and_then(|id| window_geometries.get(id));
             winit_windows.push((
-                window_config,
-                Self::create_winit_window(event_loop, &window_config)?,
+                window_config.clone(),
+                Self::create_winit_window(event_loop, window_config, geometry)?,
             ))
         }
-        // Modify features & limits needed for common pipelines
-        // Add push constants feature for common pipelines
-        config.device_config.features |= wgpu::Features::PUSH_CONSTANTS
-            | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
-        config.device_config.limits = wgpu::Limits {
-            ..config.device_config.limits
-        };
         let device_context = DeviceContext::new(
-            &config.device_config,
+            &Self::pipeline_device_config(config.device_config),
             // Needed to ensure our queue families are compatible with surface
             &winit_windows,
         )?;
         let mut app = Self {
             device_context,
             windows: IndexMap::default(),
+            fixed_timestep: config.fixed_update_rate.map(FixedTimestep::new),
+            frame_timer: FrameTimer::default(),
+            event_loop_proxy: Some((Box::new(event_loop.create_proxy()), type_name::<E>())),
+            control_flow_policy: config.control_flow_policy.validated(),
+            created_windows: vec![],
+            pending_window_closes: vec![],
+            states: HashMap::default(),
+            actions: ActionMap::default(),
+            offscreen_inputs: vec![],
+            offscreen_formats: vec![],
+            window_geometry_file: config.window_geometry_file,
+            window_geometries,
+            recorder: None,
+            replay: None,
             exit: false,
         };
         for (window_config, window) in winit_windows {
@@ -278,6 +563,54 @@ impl GlassContext {
         Ok(app)
     }
 
+    /// Creates a context without an event loop and without windows. Used by
+    /// [`HeadlessGlass`](crate::HeadlessGlass), which renders to offscreen targets instead.
+    pub(crate) fn new_headless(config: GlassConfig) -> Result<Self, GlassError> {
+        let device_context =
+            DeviceContext::new(&Self::pipeline_device_config(config.device_config), &[])?;
+        // Without surfaces, offscreen targets use the most preferred format
+        let offscreen_formats = config
+            .window_configs
+            .iter()
+            .map(|c| {
+                c.surface_formats
+                    .first()
+                    .copied()
+                    .unwrap_or(DEFAULT_SURFACE_FORMATS[0])
+            })
+            .collect();
+        Ok(Self {
+            device_context,
+            windows: IndexMap::default(),
+            fixed_timestep: config.fixed_update_rate.map(FixedTimestep::new),
+            frame_timer: FrameTimer::default(),
+            event_loop_proxy: None,
+            control_flow_policy: config.control_flow_policy.validated(),
+            created_windows: vec![],
+            pending_window_closes: vec![],
+            states: HashMap::default(),
+            actions: ActionMap::default(),
+            offscreen_inputs: vec![],
+            offscreen_formats,
+            window_geometry_file: config.window_geometry_file,
+            window_geometries: WindowGeometries::default(),
+            recorder: None,
+            replay: None,
+            exit: false,
+        })
+    }
+
+    /// Request features & limits used by common pipelines, if the adapter supports them
+    fn pipeline_device_config(mut device_config: DeviceConfig) -> DeviceConfig {
+        device_config.optional_features |= wgpu::Features::PUSH_CONSTANTS
+            | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+        device_config.optional_limits.max_push_constant_size = device_config
+            .optional_limits
+            .max_push_constant_size
+            .max(128);
+        device_config
+    }
+
     #[allow(unused)]
     pub fn instance(&self) -> &Instance {
         self.device_context.instance()
@@ -295,6 +628,52 @@ impl GlassContext {
         self.device_context.queue()
     }
 
+    /// Submit command buffers to the queue, see [`DeviceContext::submit`]
+    pub fn submit<I: IntoIterator<Item = CommandBuffer>>(
+        &self,
+        command_buffers: I,
+    ) -> Result<SubmissionIndex, GlassError> {
+        self.device_context.submit(command_buffers)
+    }
+
+    pub(crate) fn device_context(&self) -> &DeviceContext {
+        &self.device_context
+    }
+
+    /// Take wgpu errors collected with
+    /// [`GpuErrorMode::Collect`](crate::device_context::GpuErrorMode::Collect)
+    pub fn take_gpu_errors(&self) -> Vec<GlassError> {
+        self.device_context.take_errors()
+    }
+
+    /// Capture validation errors until [`GlassContext::pop_error_scope`]
+    pub(crate) fn push_error_scope(&self) {
+        self.device_context.push_error_scope();
+    }
+
+    /// Report validation error captured since [`GlassContext::push_error_scope`] with the stage &
+    /// window it happened in
+    pub(crate) fn pop_error_scope(&self, stage: GlassStage, window_id: Option<WindowId>) {
+        self.device_context.pop_error_scope(stage, window_id);
+    }
+
+    /// Features granted to the device. Optional features are included if the adapter supports
+    /// them.
+    pub fn features(&self) -> wgpu::Features {
+        self.device_context.features()
+    }
+
+    /// Limits granted to the device
+    pub fn limits(&self) -> wgpu::Limits {
+        self.device_context.limits()
+    }
+
+    /// Whether the device has been lost. It's recreated at the start of the next frame, followed
+    /// by [`GlassApp::device_recreated`].
+    pub fn is_device_lost(&self) -> bool {
+        self.device_context.is_lost()
+    }
+
     pub fn configure_surface(&mut self, window_id: &WindowId, config: &SurfaceConfiguration) {
         if let Some(window) = self.windows.get_mut(window_id) {
             window.configure_surface(self.device_context.device(), config);
@@ -319,19 +698,25 @@ impl GlassContext {
         self.windows.get_mut(&id)
     }
 
-    pub fn create_window(
+    pub fn create_window<E: 'static>(
         &mut self,
-        event_loop: &EventLoopWindowTarget<()>,
+        event_loop: &EventLoopWindowTarget<E>,
         config: WindowConfig,
     ) -> Result<WindowId, GlassError> {
         let reconfigure_device = self.windows.is_empty();
-        let window = Self::create_winit_window(event_loop, &config)?;
+        let geometry = config
+            .persistence_id
+            .as_ref()
+            .and_then(|id| self.window_geometries.get(id));
+        let window = Self::create_winit_window(event_loop, &config, geometry)?;
         let id = self.add_window(config, window)?;
         // Reconfigure devices with surface so queue families are correct
         let window = self.windows.get_mut(&id).unwrap();
         if reconfigure_device {
             let surface = window.surface();
             self.device_context.reconfigure_with_surface(surface)?;
+            // Surface capabilities depend on the adapter
+            window.negotiate_surface(self.device_context.adapter());
         }
         // Configure surface with size
         window.configure_surface_with_size(
@@ -341,23 +726,63 @@ impl GlassContext {
         Ok(id)
     }
 
+    /// Recreate device & window surfaces after the device was lost
+    pub(crate) fn recreate_device(&mut self) -> Result<(), GlassError> {
+        let compatible_window = self.windows.values().next().map(|w| w.window());
+        self.device_context.recreate(compatible_window)?;
+        for (id, window) in self.windows.iter_mut() {
+            window.recreate_surface(&self.device_context).map_err(|e| {
+                GlassError::SurfaceError {
+                    window_id: *id,
+                    source: e,
+                }
+            })?;
+            window.set_needs_redraw(true);
+        }
+        Ok(())
+    }
+
     fn add_window(&mut self, config: WindowConfig, window: Window) -> Result<WindowId, GlassError> {
         let id = window.id();
         let render_window = match GlassWindow::new(&self.device_context, config, window) {
             Ok(window) => window,
-            Err(e) => return Err(GlassError::SurfaceError(e)),
+            Err(e) => {
+                return Err(GlassError::SurfaceError {
+                    window_id: id,
+                    source: e,
+                })
+            }
         };
         self.windows.insert(id, render_window);
+        self.created_windows.push(id);
         Ok(id)
     }
 
-    fn create_winit_window(
-        event_loop: &EventLoopWindowTarget<()>,
+    fn create_winit_window<E: 'static>(
+        event_loop: &EventLoopWindowTarget<E>,
         config: &WindowConfig,
+        geometry: Option<&WindowGeometry>,
     ) -> Result<Window, GlassError> {
         let mut window_builder = winit::window::WindowBuilder::new()
-            .with_inner_size(winit::dpi::LogicalSize::new(config.width, config.height))
-            .with_title(config.title);
+            .with_inner_size(LogicalSize::new(config.width, config.height))
+            .with_title(&config.title)
+            .with_window_icon(config.icon.clone())
+            .with_decorations(config.decorations)
+            .with_resizable(config.resizable)
+            .with_transparent(config.transparent)
+            .with_visible(config.visible)
+            .with_window_level(config.window_level);
+
+        // App id, e.g. for grouping windows & matching desktop entries
+        #[cfg(all(
+            unix,
+            not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+        ))]
+        if let Some(app_id) = &config.app_id {
+            use winit::platform::x11::WindowBuilderExtX11;
+            // Sets the Wayland app id too
+            window_builder = window_builder.with_name(app_id, app_id);
+        }
 
         // Min size
         if let Some(inner_size) = config.min_size {
@@ -369,48 +794,313 @@ impl GlassContext {
             window_builder = window_builder.with_max_inner_size(inner_size);
         }
 
-        window_builder = match &config.pos {
-            WindowPos::Maximized => window_builder.with_maximized(true),
-            WindowPos::FullScreen => {
-                if let Some(monitor) = event_loop.primary_monitor() {
-                    window_builder
-                        .with_fullscreen(Some(Fullscreen::Exclusive(get_best_videomode(&monitor))))
-                } else {
-                    window_builder
-                }
+        let monitors = event_loop.available_monitors().collect::<Vec<_>>();
+        let primary = event_loop.primary_monitor();
+        if let Some(geometry) = geometry {
+            // Restore saved geometry instead of the configured placement, on connected monitors
+            let geometry = geometry.clamped(&monitor_infos(&monitors, primary.as_ref()));
+            window_builder = window_builder
+                .with_inner_size(geometry.size)
+                .with_position(geometry.position)
+                .with_maximized(geometry.maximized);
+            if geometry.fullscreen {
+                let monitor = select_monitor(&geometry.monitor, &monitors, primary.as_ref(), None);
+                window_builder =
+                    window_builder.with_fullscreen(Some(Fullscreen::Borderless(monitor)));
             }
-            WindowPos::SizedFullScreen => {
-                if let Some(monitor) = event_loop.primary_monitor() {
-                    window_builder.with_fullscreen(Some(Fullscreen::Exclusive(
-                        get_fitting_videomode(&monitor, config.width, config.height),
-                    )))
-                } else {
-                    window_builder
+        } else {
+            // Created windows are not on a monitor yet, current selects the primary one
+            let monitor = config
+                .pos
+                .monitor()
+                .and_then(|selector| select_monitor(selector, &monitors, primary.as_ref(), None));
+            let scale_factor = monitor.as_ref().map_or(1.0, |m| m.scale_factor());
+            let size = LogicalSize::new(config.width, config.height).to_physical(scale_factor);
+            window_builder = match window_placement(&config.pos, monitor.as_ref(), size) {
+                Placement::Fullscreen(fullscreen) => {
+                    window_builder.with_fullscreen(Some(fullscreen))
                 }
-            }
-            WindowPos::FullScreenBorderless => window_builder
-                .with_fullscreen(Some(Fullscreen::Borderless(event_loop.primary_monitor()))),
-            WindowPos::Pos(pos) => window_builder.with_position(*pos),
-            WindowPos::Centered => {
-                if let Some(monitor) = event_loop.primary_monitor() {
-                    window_builder.with_position(get_centered_window_position(
-                        &monitor,
-                        config.width,
-                        config.height,
-                    ))
-                } else {
-                    window_builder
+                Placement::Position(pos) => window_builder.with_position(pos),
+                Placement::Maximized(pos) => match pos {
+                    Some(pos) => window_builder.with_position(pos),
+                    None => window_builder,
                 }
-            }
-        };
+                .with_maximized(true),
+                Placement::Unchanged => window_builder,
+            };
+        }
 
         match window_builder.build(event_loop) {
             Ok(w) => Ok(w),
-            Err(e) => Err(GlassError::WindowError(e)),
+            Err(e) => Err(GlassError::WindowError {
+                title: config.title.clone(),
+                source: e,
+            }),
+        }
+    }
+
+    /// Returns a proxy to wake up the event loop and send user events to
+    /// [`GlassApp::user_event`], also from other threads. `None` when running headless.
+    ///
+    /// # Panics
+    /// If `E` is not the user event type of your [`Glass`] app.
+    pub fn event_loop_proxy<E: 'static>(&self) -> Option<EventLoopProxy<E>> {
+        let (proxy, event_type) = self.event_loop_proxy.as_ref()?;
+        let proxy = proxy
+            .downcast_ref::<EventLoopProxy<E>>()
+            .unwrap_or_else(|| {
+                panic!(
+                    "Requested event loop proxy for user events of type {}, but the app's user \
+                     events are of type {}",
+                    type_name::<E>(),
+                    event_type
+                )
+            });
+        Some(proxy.clone())
+    }
+
+    pub fn control_flow_policy(&self) -> ControlFlowPolicy {
+        self.control_flow_policy
+    }
+
+    /// # Panics
+    /// If frames per second of [`ControlFlowPolicy::WaitUntil`] are not positive.
+    pub fn set_control_flow_policy(&mut self, policy: ControlFlowPolicy) {
+        self.control_flow_policy = policy.validated();
+    }
+
+    /// Mark window to be rendered next frame. Only needed with [`ControlFlowPolicy::Reactive`].
+    pub fn request_redraw(&mut self, window_id: WindowId) {
+        if let Some(window) = self.windows.get_mut(&window_id) {
+            window.set_needs_redraw(true);
+            // Wakes up the event loop if it's waiting
+            window.request_redraw();
+        }
+    }
+
+    /// Close a window at the end of the update stage. Unlike closing through the window,
+    /// [`GlassApp::on_close_requested`] is not asked. The app exits once all windows are closed.
+    pub fn close_window(&mut self, window_id: WindowId) {
+        if !self.pending_window_closes.contains(&window_id) {
+            self.pending_window_closes.push(window_id);
+        }
+    }
+
+    /// Store typed state, e.g. to share it between plugins & the app. Returns previous state of the
+    /// same type.
+    pub fn insert_state<T: 'static>(&mut self, state: T) -> Option<T> {
+        self.states
+            .insert(TypeId::of::<T>(), Box::new(state))
+            .map(|previous| *previous.downcast::<T>().unwrap())
+    }
+
+    pub fn state<T: 'static>(&self) -> Option<&T> {
+        self.states.get(&TypeId::of::<T>())?.downcast_ref::<T>()
+    }
+
+    pub fn state_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.states.get_mut(&TypeId::of::<T>())?.downcast_mut::<T>()
+    }
+
+    pub fn remove_state<T: 'static>(&mut self) -> Option<T> {
+        self.states
+            .remove(&TypeId::of::<T>())
+            .map(|state| *state.downcast::<T>().unwrap())
+    }
+
+    /// Named input actions, updated each frame before fixed update
+    pub fn actions(&self) -> &ActionMap {
+        &self.actions
+    }
+
+    /// Access actions e.g. to change bindings or to load them from a file
+    pub fn actions_mut(&mut self) -> &mut ActionMap {
+        &mut self.actions
+    }
+
+    pub(crate) fn update_actions(&mut self) {
+        self.actions.update(
+            self.windows
+                .values()
+                .map(|window| window.input())
+                .chain(self.offscreen_inputs.iter()),
+        );
+    }
+
+    /// Input state of the primary window. When running headless, input state of the first
+    /// offscreen target, which receives replayed input.
+    pub fn primary_input(&self) -> Option<&InputState> {
+        self.windows
+            .first()
+            .map(|(_, window)| window.input())
+            .or(self.offscreen_inputs.first())
+    }
+
+    pub(crate) fn offscreen_inputs_mut(&mut self) -> &mut Vec<InputState> {
+        &mut self.offscreen_inputs
+    }
+
+    /// Surface format of the primary window, e.g. to create pipelines rendering to it. When
+    /// running headless, format of the first offscreen target.
+    pub fn primary_surface_format(&self) -> Option<TextureFormat> {
+        self.windows
+            .first()
+            .map(|(_, window)| window.surface_format())
+            .or(self.offscreen_formats.first().copied())
+    }
+
+    /// Connected monitors, e.g. to choose one for [`WindowPos`](crate::window::WindowPos) with
+    /// [`MonitorSelector`](crate::window::MonitorSelector). Empty when no monitors are reported,
+    /// e.g. on some Wayland compositors before a window exists.
+    pub fn monitors<E: 'static>(&self, event_loop: &EventLoopWindowTarget<E>) -> Vec<MonitorInfo> {
+        monitor_infos(
+            &event_loop.available_monitors().collect::<Vec<_>>(),
+            event_loop.primary_monitor().as_ref(),
+        )
+    }
+
+    /// Window geometries loaded from & saved to [`GlassConfig::window_geometry_file`]
+    pub fn window_geometries(&self) -> &WindowGeometries {
+        &self.window_geometries
+    }
+
+    /// Store the geometry of a window with a [`WindowConfig::persistence_id`]
+    pub(crate) fn store_window_geometry(&mut self, window_id: WindowId) {
+        if let Some(window) = self.windows.get(&window_id) {
+            if let Some(id) = window.persistence_id() {
+                self.window_geometries
+                    .insert(id.to_owned(), window.geometry());
+            }
+        }
+    }
+
+    /// Save the geometries of open & closed windows with a [`WindowConfig::persistence_id`] to
+    /// [`GlassConfig::window_geometry_file`]. Done automatically when the app exits. Nothing is
+    /// written if no window has a persistence id.
+    pub fn save_window_geometries(&mut self) -> Result<(), GlassError> {
+        let window_ids = self.windows.keys().copied().collect::<Vec<_>>();
+        for window_id in window_ids {
+            self.store_window_geometry(window_id);
+        }
+        match &self.window_geometry_file {
+            Some(path) if !self.window_geometries.windows.is_empty() => {
+                self.window_geometries.save(path)
+            }
+            _ => Ok(()),
         }
     }
 
+    pub(crate) fn offscreen_formats(&self) -> &[TextureFormat] {
+        &self.offscreen_formats
+    }
+
+    /// Start recording input events & frame times, replacing any ongoing recording
+    pub fn start_recording(&mut self) {
+        self.recorder = Some(InputRecorder::default());
+    }
+
+    /// Stop recording, returns the recording if one was started
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        self.recorder.take().map(|recorder| recorder.finish())
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Replay recorded input. Each following frame uses the recorded frame time & receives the
+    /// recorded events instead of live input, until the recording ends.
+    pub fn start_replay(&mut self, recording: InputRecording) {
+        self.replay = Some(InputReplay::new(recording));
+    }
+
+    pub fn stop_replay(&mut self) {
+        self.replay = None;
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Next frame of replay, ends replay after the last frame
+    pub(crate) fn next_replay_frame(&mut self) -> Option<RecordedFrame> {
+        let replay = self.replay.as_mut()?;
+        let frame = replay.next_frame().cloned();
+        if replay.is_finished() {
+            self.replay = None;
+        }
+        frame
+    }
+
+    pub(crate) fn take_created_windows(&mut self) -> Vec<WindowId> {
+        std::mem::take(&mut self.created_windows)
+    }
+
     pub fn exit(&mut self) {
         self.exit = true;
     }
+
+    pub(crate) fn exit_requested(&self) -> bool {
+        self.exit
+    }
+
+    /// Duration of a fixed update step, if [`GlassConfig::fixed_update_rate`] is set.
+    pub fn fixed_timestep(&self) -> Option<Duration> {
+        self.fixed_timestep.map(|f| f.timestep())
+    }
+
+    /// Interpolation factor between the previous and the next fixed update in range `[0, 1)`.
+    /// Use it in [`GlassApp::render`] to interpolate simulation state for smooth visuals.
+    /// Returns `1.0` when no fixed update rate is set.
+    pub fn interpolation_alpha(&self) -> f32 {
+        self.fixed_timestep.map_or(1.0, |f| f.alpha() as f32)
+    }
+
+    /// Frame timing & statistics
+    pub fn frame_timer(&self) -> &FrameTimer {
+        &self.frame_timer
+    }
+
+    /// Index of the current frame, starting from zero.
+    pub fn frame_index(&self) -> u64 {
+        self.frame_timer.frame_index()
+    }
+
+    /// Time between the start of the previous frame and the current frame.
+    pub fn delta_time(&self) -> Duration {
+        self.frame_timer.delta()
+    }
+
+    /// Frames per second, smoothed over recent frames.
+    pub fn fps(&self) -> f64 {
+        self.frame_timer.fps()
+    }
+
+    pub(crate) fn frame_timer_mut(&mut self) -> &mut FrameTimer {
+        &mut self.frame_timer
+    }
+
+    /// Add time elapsed since `start` to the stage's frame time
+    pub(crate) fn add_stage_time(&mut self, stage: GlassStage, start: Instant) {
+        self.frame_timer.add_stage_time(stage, start.elapsed());
+    }
+
+    /// Add frame time to fixed update accumulator, returns the number of fixed updates to run.
+    pub(crate) fn accumulate_fixed_time(&mut self, delta: Duration) -> u32 {
+        self.fixed_timestep
+            .as_mut()
+            .map_or(0, |fixed_timestep| fixed_timestep.accumulate(delta))
+    }
+}
+
+/// Saved window geometries, empty if the file does not exist yet or cannot be loaded
+fn load_window_geometries(path: Option<&Path>) -> WindowGeometries {
+    match path {
+        // Windows are placed as configured instead, the file is overwritten on exit
+        Some(path) if path.exists() => WindowGeometries::load(path).unwrap_or_else(|e| {
+            log::warn!("Ignoring saved window geometries: {}", e);
+            WindowGeometries::default()
+        }),
+        _ => WindowGeometries::default(),
+    }
 }
@@ -1,67 +1,263 @@
-use wgpu::{CommandEncoder, SurfaceTexture};
+use std::fmt::Formatter;
+
+use wgpu::{CommandEncoder, Texture};
 use winit::{
+    dpi::PhysicalSize,
     event::Event,
     event_loop::{EventLoop, EventLoopWindowTarget},
+    window::WindowId,
 };
 
-use crate::{window::GlassWindow, GlassContext};
+use crate::{window::GlassWindow, GlassContext, GlassError};
 
 /// All necessary data required to render with wgpu. This data only lives for the duration of
 /// rendering.
 /// The command queue will be submitted each frame.
 pub struct RenderData<'a> {
     pub encoder: &'a mut CommandEncoder,
-    pub window: &'a GlassWindow,
-    pub frame: &'a SurfaceTexture,
+    pub target: RenderTarget<'a>,
+    /// Surface texture of the window, or the offscreen texture
+    pub frame: &'a Texture,
+}
+
+/// What is rendered to, a window's surface or an offscreen texture of
+/// [`HeadlessGlass`](crate::HeadlessGlass)
+#[derive(Copy, Clone)]
+pub enum RenderTarget<'a> {
+    Window(&'a GlassWindow),
+    /// Index of the offscreen target, in the order of window configs
+    Offscreen(usize),
+}
+
+impl<'a> RenderTarget<'a> {
+    pub fn window(&self) -> Option<&'a GlassWindow> {
+        match self {
+            RenderTarget::Window(window) => Some(window),
+            RenderTarget::Offscreen(_) => None,
+        }
+    }
+
+    pub fn window_id(&self) -> Option<WindowId> {
+        self.window().map(|window| window.window().id())
+    }
+
+    /// Scale factor of the window, 1.0 for offscreen targets
+    pub fn scale_factor(&self) -> f64 {
+        self.window()
+            .map_or(1.0, |window| window.window().scale_factor())
+    }
+}
+
+/// Stages of [`GlassApp`], used to identify the stage e.g. in frame timings.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GlassStage {
+    Start,
+    Input,
+    UserEvent,
+    Resized,
+    DeviceRecreated,
+    FixedUpdate,
+    Update,
+    Render,
+    PostProcessing,
+    AfterRender,
+    EndOfFrame,
+    End,
+}
+
+impl GlassStage {
+    pub const COUNT: usize = 12;
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GlassStage::Start => "start",
+            GlassStage::Input => "input",
+            GlassStage::UserEvent => "user_event",
+            GlassStage::Resized => "resized",
+            GlassStage::DeviceRecreated => "device_recreated",
+            GlassStage::FixedUpdate => "fixed_update",
+            GlassStage::Update => "update",
+            GlassStage::Render => "render",
+            GlassStage::PostProcessing => "post_processing",
+            GlassStage::AfterRender => "after_render",
+            GlassStage::EndOfFrame => "end_of_frame",
+            GlassStage::End => "end",
+        }
+    }
+}
+
+impl std::fmt::Display for GlassStage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
 }
 
 /// A trait to define all stages of your Glass app. Each function here is run at a specific stage
 /// within winit event loop. When you impl this for your app, think of this as the
 /// table of contents of your app flow.
-pub trait GlassApp {
-    /// Run at start
-    fn start(&mut self, _event_loop: &EventLoop<()>, _context: &mut GlassContext) {}
+///
+/// `E` is the type of user events your app receives in [`GlassApp::user_event`].
+///
+/// Stages that can fail have a `try_` variant returning a [`GlassError`], which by default runs
+/// the infallible stage. Implement the `try_` variant instead to return errors, e.g. from loading
+/// shaders in `try_start`. [`Glass::run`](crate::Glass::run) then stops, runs `end` and returns
+/// the error. Wrap your own errors with [`GlassError::app`].
+pub trait GlassApp<E: 'static = ()> {
+    /// Run at start. The event loop is `None` when running with
+    /// [`HeadlessGlass`](crate::HeadlessGlass).
+    fn start(&mut self, _event_loop: Option<&EventLoop<E>>, _context: &mut GlassContext) {}
     /// Run on each event received from winit
     fn input(
         &mut self,
         _context: &mut GlassContext,
-        _event_loop: &EventLoopWindowTarget<()>,
-        _event: &Event<()>,
+        _event_loop: &EventLoopWindowTarget<E>,
+        _event: &Event<E>,
+    ) {
+    }
+    /// Run on each user event sent through
+    /// [`GlassContext::event_loop_proxy`](crate::GlassContext::event_loop_proxy)
+    fn user_event(&mut self, _context: &mut GlassContext, _event: E) {}
+    /// Run when a window was resized or its scale factor changed, after its surface has been
+    /// reconfigured. Use it to resize render targets. A minimized window is reported with a zero
+    /// size, its surface is left as is and it won't be rendered until restored.
+    fn resized(
+        &mut self,
+        _context: &mut GlassContext,
+        _window_id: WindowId,
+        _new_size: PhysicalSize<u32>,
+        _scale_factor: f64,
     ) {
     }
+    /// Run zero or more times each frame before update at the rate of
+    /// [`GlassConfig::fixed_update_rate`](crate::GlassConfig::fixed_update_rate)
+    fn fixed_update(&mut self, _context: &mut GlassContext) {}
     /// Run each frame
     fn update(&mut self, _context: &mut GlassContext) {}
-    /// Run each frame for each window after update
+    /// Run each frame for each window, or offscreen target, after update
     fn render(&mut self, _context: &GlassContext, _render_data: RenderData) {
-        let RenderData {
-            encoder,
-            frame,
-            ..
-        } = _render_data;
-        let view = frame
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-        {
-            let _r = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                        store: true,
-                    },
-                })],
-                depth_stencil_attachment: None,
-            });
-        }
+        clear_frame(_render_data.encoder, _render_data.frame);
     }
     /// Run each frame for each window after rendering per window
     fn post_processing(&mut self, _context: &GlassContext, _render_data: RenderData) {}
     /// Run each frame for each window after post processing
     fn after_render(&mut self, _context: &GlassContext) {}
+    /// Run when a window was created, either at start or with
+    /// [`GlassContext::create_window`](crate::GlassContext::create_window)
+    fn on_window_created(&mut self, _context: &mut GlassContext, _window_id: WindowId) {}
+    /// Run when a window is requested to be closed by the user. Return `false` to keep the window
+    /// open, e.g. to prompt about unsaved work first. Close it later with
+    /// [`GlassContext::close_window`](crate::GlassContext::close_window).
+    fn on_close_requested(&mut self, _context: &mut GlassContext, _window_id: WindowId) -> bool {
+        true
+    }
+    /// Run when a window is closed, just before it is removed from the context. Also run for
+    /// each remaining window at exit.
+    fn on_window_closed(&mut self, _context: &mut GlassContext, _window_id: WindowId) {}
+    /// Run when acquiring the next frame of a window timed out. The window is not rendered
+    /// this frame.
+    fn surface_timeout(&mut self, _context: &mut GlassContext, _window_id: WindowId) {}
+    /// Run at the start of a frame after the device was lost and has been recreated along with
+    /// window surfaces. Resources of the old device are invalid, rebuild pipelines, buffers &
+    /// textures here.
+    fn device_recreated(&mut self, _context: &mut GlassContext) {}
     /// Run each frame last
     fn end_of_frame(&mut self, _context: &mut GlassContext) {}
     /// Run at exit
     fn end(&mut self, _context: &mut GlassContext) {}
+
+    /// Fallible [`GlassApp::start`]
+    fn try_start(
+        &mut self,
+        event_loop: Option<&EventLoop<E>>,
+        context: &mut GlassContext,
+    ) -> Result<(), GlassError> {
+        self.start(event_loop, context);
+        Ok(())
+    }
+    /// Fallible [`GlassApp::input`]
+    fn try_input(
+        &mut self,
+        context: &mut GlassContext,
+        event_loop: &EventLoopWindowTarget<E>,
+        event: &Event<E>,
+    ) -> Result<(), GlassError> {
+        self.input(context, event_loop, event);
+        Ok(())
+    }
+    /// Fallible [`GlassApp::user_event`]
+    fn try_user_event(&mut self, context: &mut GlassContext, event: E) -> Result<(), GlassError> {
+        self.user_event(context, event);
+        Ok(())
+    }
+    /// Fallible [`GlassApp::resized`]
+    fn try_resized(
+        &mut self,
+        context: &mut GlassContext,
+        window_id: WindowId,
+        new_size: PhysicalSize<u32>,
+        scale_factor: f64,
+    ) -> Result<(), GlassError> {
+        self.resized(context, window_id, new_size, scale_factor);
+        Ok(())
+    }
+    /// Fallible [`GlassApp::fixed_update`]
+    fn try_fixed_update(&mut self, context: &mut GlassContext) -> Result<(), GlassError> {
+        self.fixed_update(context);
+        Ok(())
+    }
+    /// Fallible [`GlassApp::update`]
+    fn try_update(&mut self, context: &mut GlassContext) -> Result<(), GlassError> {
+        self.update(context);
+        Ok(())
+    }
+    /// Fallible [`GlassApp::render`]
+    fn try_render(
+        &mut self,
+        context: &GlassContext,
+        render_data: RenderData,
+    ) -> Result<(), GlassError> {
+        self.render(context, render_data);
+        Ok(())
+    }
+    /// Fallible [`GlassApp::post_processing`]
+    fn try_post_processing(
+        &mut self,
+        context: &GlassContext,
+        render_data: RenderData,
+    ) -> Result<(), GlassError> {
+        self.post_processing(context, render_data);
+        Ok(())
+    }
+    /// Fallible [`GlassApp::after_render`]
+    fn try_after_render(&mut self, context: &GlassContext) -> Result<(), GlassError> {
+        self.after_render(context);
+        Ok(())
+    }
+    /// Fallible [`GlassApp::device_recreated`]
+    fn try_device_recreated(&mut self, context: &mut GlassContext) -> Result<(), GlassError> {
+        self.device_recreated(context);
+        Ok(())
+    }
+    /// Fallible [`GlassApp::end_of_frame`]
+    fn try_end_of_frame(&mut self, context: &mut GlassContext) -> Result<(), GlassError> {
+        self.end_of_frame(context);
+        Ok(())
+    }
+}
+
+/// Clear the frame to black, what apps not rendering anything show
+fn clear_frame(encoder: &mut CommandEncoder, frame: &Texture) {
+    let view = frame.create_view(&wgpu::TextureViewDescriptor::default());
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: None,
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: &view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                store: true,
+            },
+        })],
+        depth_stencil_attachment: None,
+    });
 }
@@ -0,0 +1,76 @@
+use winit::{
+    dpi::PhysicalSize,
+    event::Event,
+    event_loop::{EventLoop, EventLoopWindowTarget},
+    window::WindowId,
+};
+
+use crate::{GlassContext, RenderData};
+
+/// A reusable piece of functionality, e.g. a gui integration or an fps counter, that hooks into
+/// the same stages as [`GlassApp`](crate::GlassApp). Add plugins with
+/// [`Glass::with_plugin`](crate::Glass::with_plugin) or
+/// [`HeadlessGlass::with_plugin`](crate::HeadlessGlass::with_plugin). Plugins can share typed
+/// state with the app and other plugins through [`GlassContext::insert_state`].
+///
+/// Plugins run in the order they were added:
+/// - In `start`, `input`, `user_event`, `resized`, `on_window_created`, `device_recreated`,
+///   `fixed_update` and `update` plugins run before the app, so the app sees their state up to
+///   date.
+/// - In `render`, `post_processing`, `after_render`, `surface_timeout` and `end_of_frame` plugins
+///   run after the app, e.g. to draw overlays on top of the app.
+/// - In `on_window_closed` and `end` plugins run after the app in reverse order, so the app can
+///   still use plugin state while tearing down.
+/// - A window is closed only if the app and all plugins agree in `on_close_requested`.
+pub trait GlassPlugin<E: 'static = ()> {
+    /// Run at start. The event loop is `None` when running with
+    /// [`HeadlessGlass`](crate::HeadlessGlass).
+    fn start(&mut self, _event_loop: Option<&EventLoop<E>>, _context: &mut GlassContext) {}
+    /// Run on each event received from winit
+    fn input(
+        &mut self,
+        _context: &mut GlassContext,
+        _event_loop: &EventLoopWindowTarget<E>,
+        _event: &Event<E>,
+    ) {
+    }
+    /// Run on each user event, before the app receives it
+    fn user_event(&mut self, _context: &mut GlassContext, _event: &E) {}
+    /// Run when a window was resized or its scale factor changed
+    fn resized(
+        &mut self,
+        _context: &mut GlassContext,
+        _window_id: WindowId,
+        _new_size: PhysicalSize<u32>,
+        _scale_factor: f64,
+    ) {
+    }
+    /// Run zero or more times each frame before update
+    fn fixed_update(&mut self, _context: &mut GlassContext) {}
+    /// Run each frame
+    fn update(&mut self, _context: &mut GlassContext) {}
+    /// Run each frame for each window, or offscreen target, after the app has rendered
+    fn render(&mut self, _context: &GlassContext, _render_data: RenderData) {}
+    /// Run each frame for each window, or offscreen target, after the app's post processing
+    fn post_processing(&mut self, _context: &GlassContext, _render_data: RenderData) {}
+    /// Run each frame for each window after post processing
+    fn after_render(&mut self, _context: &GlassContext) {}
+    /// Run when a window was created
+    fn on_window_created(&mut self, _context: &mut GlassContext, _window_id: WindowId) {}
+    /// Run when a window is requested to be closed by the user. Return `false` to keep the window
+    /// open.
+    fn on_close_requested(&mut self, _context: &mut GlassContext, _window_id: WindowId) -> bool {
+        true
+    }
+    /// Run when a window is closed, just before it is removed from the context
+    fn on_window_closed(&mut self, _context: &mut GlassContext, _window_id: WindowId) {}
+    /// Run when acquiring the next frame of a window timed out
+    fn surface_timeout(&mut self, _context: &mut GlassContext, _window_id: WindowId) {}
+    /// Run after the device was lost and has been recreated, before the app rebuilds its
+    /// resources
+    fn device_recreated(&mut self, _context: &mut GlassContext) {}
+    /// Run each frame last
+    fn end_of_frame(&mut self, _context: &mut GlassContext) {}
+    /// Run at exit
+    fn end(&mut self, _context: &mut GlassContext) {}
+}
@@ -0,0 +1,525 @@
+use std::time::Duration;
+
+use image::RgbaImage;
+use wgpu::{
+    AddressMode, Extent3d, FilterMode, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout,
+    Origin3d, SamplerDescriptor, TextureAspect, TextureFormat, TextureUsages,
+};
+
+use crate::{
+    input::InputState, runner::GlassRunner, texture::Texture, GlassApp, GlassConfig, GlassContext,
+    GlassError, GlassPlugin, GlassStage, RenderTarget,
+};
+
+/// [`HeadlessGlass`] runs your [`GlassApp`] without winit. Instead of window surfaces, each frame
+/// is rendered to offscreen targets, one per [`WindowConfig`](crate::window::WindowConfig) in
+/// your [`GlassConfig`], with its most preferred surface format
+/// ([`GlassContext::primary_surface_format`]). Frames are stepped on demand, which makes it
+/// possible to run apps in CI or batch jobs where no display is available.
+///
+/// Stages run are `start`, `fixed_update`, `update`, `render`, `post_processing`, `after_render`,
+/// `end_of_frame` and `end`, plus `device_recreated` if the device was lost. `start` receives no
+/// event loop, render stages receive [`RenderTarget::Offscreen`], and `input` is never run,
+/// because there is no event loop.
+///
+/// Each step advances time by a constant [`HeadlessGlass::frame_delta`] instead of wall clock
+/// time, so runs are deterministic. User events are delivered with
+/// [`HeadlessGlass::send_user_event`].
+///
+/// Plugins are added with [`HeadlessGlass::with_plugin`] before the first step, which runs
+/// `start`.
+///
+/// Input recorded with [`Glass`](crate::Glass) can be replayed with
+/// [`GlassContext::start_replay`]. Each step then uses the recorded frame time, and recorded
+/// events update the input state of the offscreen targets
+/// ([`GlassContext::primary_input`]) & [`GlassContext::actions`].
+pub struct HeadlessGlass<A, E: 'static = ()> {
+    runner: GlassRunner<A, E>,
+    context: GlassContext,
+    targets: Vec<Texture>,
+    frame_delta: Duration,
+    started: bool,
+    ended: bool,
+}
+
+impl<A: GlassApp<E>, E: 'static> HeadlessGlass<A, E> {
+    /// Creates device & offscreen targets. The app is started with the first step.
+    pub fn new(app: A, config: GlassConfig) -> Result<HeadlessGlass<A, E>, GlassError> {
+        let targets = config
+            .window_configs
+            .iter()
+            .map(|window_config| (window_config.width, window_config.height))
+            .collect::<Vec<_>>();
+        let mut context = GlassContext::new_headless(config)?;
+        let targets = targets
+            .into_iter()
+            .zip(context.offscreen_formats())
+            .map(|((width, height), &format)| {
+                create_offscreen_target(&context, width, height, format)
+            })
+            .collect::<Vec<_>>();
+        context
+            .offscreen_inputs_mut()
+            .resize_with(targets.len(), InputState::default);
+        // Default to one fixed step per frame, or 60 fps
+        let frame_delta = context
+            .fixed_timestep()
+            .unwrap_or(Duration::from_secs_f64(1.0 / 60.0));
+        Ok(HeadlessGlass {
+            runner: GlassRunner::new(app),
+            context,
+            targets,
+            frame_delta,
+            started: false,
+            ended: false,
+        })
+    }
+
+    /// Add a plugin, which is run around the app's stages as described in [`GlassPlugin`].
+    ///
+    /// # Panics
+    /// If a frame has been run already, since the plugin would miss `start`.
+    pub fn with_plugin(mut self, plugin: impl GlassPlugin<E> + 'static) -> HeadlessGlass<A, E> {
+        assert!(
+            !self.started,
+            "Plugins must be added before the first step of HeadlessGlass"
+        );
+        self.runner.add_plugin(Box::new(plugin));
+        self
+    }
+
+    /// Run a single frame, starting the app first if needed. Does nothing once the app has
+    /// exited. If a stage of the app fails, `end` is run and the error is returned.
+    pub fn step(&mut self) -> Result<(), GlassError> {
+        if self.ended {
+            return Ok(());
+        }
+        let result = self.start().and_then(|_| self.run_frame());
+        if result.is_err() {
+            self.end();
+        }
+        result
+    }
+
+    fn run_frame(&mut self) -> Result<(), GlassError> {
+        // Rebuild device & offscreen targets if the device was lost
+        if self.context.is_device_lost() {
+            self.context.recreate_device()?;
+            self.targets = self
+                .targets
+                .iter()
+                .map(|target| {
+                    let texture = &target.texture;
+                    create_offscreen_target(
+                        &self.context,
+                        texture.width(),
+                        texture.height(),
+                        texture.format(),
+                    )
+                })
+                .collect();
+            self.runner.device_recreated(&mut self.context)?;
+        }
+        let mut delta = self.frame_delta;
+        // Replay recorded input with recorded frame time
+        if let Some(frame) = self.context.next_replay_frame() {
+            delta = frame.delta;
+            for recorded in frame.events {
+                if let Some(input) = self.context.offscreen_inputs_mut().get_mut(recorded.window) {
+                    input.process_recorded_event(&recorded.event);
+                }
+            }
+        }
+        self.context.frame_timer_mut().begin_frame(delta);
+        self.context.update_actions();
+        self.runner.fixed_update(&mut self.context)?;
+        self.runner.update(&mut self.context)?;
+        if self.context.exit_requested() {
+            self.end();
+            return Ok(());
+        }
+        for (index, target) in self.targets.iter().enumerate() {
+            let mut encoder =
+                self.context
+                    .device()
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: Some("Render Commands"),
+                    });
+
+            // Run render & post processing functions
+            let render_target = RenderTarget::Offscreen(index);
+            let render_time =
+                self.runner
+                    .render(&self.context, &mut encoder, render_target, &target.texture)?;
+            let post_processing_time = self.runner.post_processing(
+                &self.context,
+                &mut encoder,
+                render_target,
+                &target.texture,
+            )?;
+
+            // Device is recreated next step
+            if self.context.submit(Some(encoder.finish())).is_err() {
+                break;
+            }
+
+            let after_render_time = self.runner.after_render(&self.context)?;
+
+            let timer = self.context.frame_timer_mut();
+            timer.add_stage_time(GlassStage::Render, render_time);
+            timer.add_stage_time(GlassStage::PostProcessing, post_processing_time);
+            timer.add_stage_time(GlassStage::AfterRender, after_render_time);
+        }
+        // End of frame
+        self.runner.end_of_frame(&mut self.context)?;
+        for input in self.context.offscreen_inputs_mut() {
+            input.end_frame();
+        }
+        self.context.frame_timer_mut().end_frame();
+        Ok(())
+    }
+
+    /// Run [`GlassApp::user_event`] immediately with `event`. Does nothing once the app has
+    /// exited. If it fails, `end` is run and the error is returned.
+    pub fn send_user_event(&mut self, event: E) -> Result<(), GlassError> {
+        if self.ended {
+            return Ok(());
+        }
+        let result = self
+            .start()
+            .and_then(|_| self.runner.user_event(&mut self.context, event));
+        if result.is_err() {
+            self.end();
+        }
+        result
+    }
+
+    /// Run `num_frames` frames, or until the app exits or fails.
+    pub fn run_frames(&mut self, num_frames: usize) -> Result<(), GlassError> {
+        for _ in 0..num_frames {
+            if self.ended {
+                break;
+            }
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    /// Run `start` once
+    fn start(&mut self) -> Result<(), GlassError> {
+        if !self.started {
+            self.started = true;
+            self.runner.start(None, &mut self.context)?;
+        }
+        Ok(())
+    }
+
+    /// Run `end` stage, if the app was started. Stepping after this does nothing.
+    pub fn end(&mut self) {
+        if !self.ended {
+            if self.started {
+                self.runner.end(&mut self.context);
+            }
+            self.ended = true;
+        }
+    }
+
+    /// Whether the app has exited, either by calling [`GlassContext::exit`] or through
+    /// [`HeadlessGlass::end`].
+    pub fn has_ended(&self) -> bool {
+        self.ended
+    }
+
+    /// Time advanced by each step.
+    pub fn frame_delta(&self) -> Duration {
+        self.frame_delta
+    }
+
+    pub fn set_frame_delta(&mut self, frame_delta: Duration) {
+        self.frame_delta = frame_delta;
+    }
+
+    /// Number of frames stepped so far.
+    pub fn frame_count(&self) -> u64 {
+        self.context.frame_index()
+    }
+
+    /// Offscreen targets rendered to each frame, in the order of window configs.
+    pub fn targets(&self) -> &[Texture] {
+        &self.targets
+    }
+
+    /// Copy the contents of an offscreen target back to the cpu. Waits for the gpu to finish.
+    /// [`TextureFormat::Rgba16Float`] targets, e.g. from
+    /// [`HDR_SURFACE_FORMATS`](crate::window::HDR_SURFACE_FORMATS), are clamped to 0-1 and
+    /// encoded as srgb. Other formats return [`GlassError::TextureReadError`].
+    pub fn read_target(&self, index: usize) -> Result<RgbaImage, GlassError> {
+        read_texture_rgba8(&self.context, &self.targets[index].texture)
+    }
+
+    pub fn app(&self) -> &A {
+        self.runner.app()
+    }
+
+    pub fn app_mut(&mut self) -> &mut A {
+        self.runner.app_mut()
+    }
+
+    pub fn context(&self) -> &GlassContext {
+        &self.context
+    }
+
+    pub fn context_mut(&mut self) -> &mut GlassContext {
+        &mut self.context
+    }
+}
+
+fn create_offscreen_target(
+    context: &GlassContext,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+) -> Texture {
+    Texture::empty(
+        context.device(),
+        "Offscreen Target",
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        1,
+        format,
+        &SamplerDescriptor {
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Linear,
+            ..Default::default()
+        },
+        TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC,
+    )
+}
+
+/// Read 8 bit rgba or bgra, or 16 bit float rgba texture into an rgba image.
+fn read_texture_rgba8(
+    context: &GlassContext,
+    texture: &wgpu::Texture,
+) -> Result<RgbaImage, GlassError> {
+    let (is_bgra, bytes_per_pixel) = match texture.format() {
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => (true, 4),
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => (false, 4),
+        TextureFormat::Rgba16Float => (false, 8),
+        format => {
+            return Err(GlassError::TextureReadError {
+                format,
+            })
+        }
+    };
+    let (width, height) = (texture.width(), texture.height());
+    // Rows must be aligned to wgpu::COPY_BYTES_PER_ROW_ALIGNMENT
+    let unpadded_bytes_per_row = width * bytes_per_pixel;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+    let size = (padded_bytes_per_row * height) as u64;
+    let buffer = context.device().create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Offscreen Readback Buffer"),
+        size,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = context
+        .device()
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Readback Commands"),
+        });
+    encoder.copy_texture_to_buffer(
+        ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+            aspect: TextureAspect::All,
+        },
+        ImageCopyBuffer {
+            buffer: &buffer,
+            layout: ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: None,
+            },
+        },
+        texture.size(),
+    );
+    let submission = context.submit(Some(encoder.finish()))?;
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    context.device_context().wait_for(submission)?;
+    receiver
+        .recv()
+        .expect("Readback channel closed")
+        .map_err(|e| GlassError::BufferReadError {
+            size,
+            source: e,
+        })?;
+
+    let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    {
+        let data = slice.get_mapped_range();
+        for row in data.chunks(padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+    }
+    buffer.unmap();
+    if bytes_per_pixel == 8 {
+        pixels = linear_f16_to_srgb8(&pixels);
+    }
+    if is_bgra {
+        for pixel in pixels.chunks_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    Ok(RgbaImage::from_raw(width, height, pixels).unwrap())
+}
+
+/// Convert linear 16 bit float channels to srgb encoded bytes, alpha stays linear
+fn linear_f16_to_srgb8(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .chunks(2)
+        .enumerate()
+        .map(|(i, half)| {
+            let value = f16_to_f32(u16::from_le_bytes([half[0], half[1]]));
+            let value = if value.is_nan() {
+                0.0
+            } else {
+                value.clamp(0.0, 1.0)
+            };
+            let value = if i % 4 == 3 {
+                value
+            } else if value <= 0.0031308 {
+                value * 12.92
+            } else {
+                1.055 * value.powf(1.0 / 2.4) - 0.055
+            };
+            (value * 255.0).round() as u8
+        })
+        .collect()
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f32;
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => sign * f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use wgpu::{Extent3d, SamplerDescriptor, TextureFormat, TextureUsages};
+    use winit::event_loop::EventLoop;
+
+    use crate::{
+        headless::{f16_to_f32, linear_f16_to_srgb8, read_texture_rgba8},
+        texture::Texture,
+        GlassApp, GlassConfig, GlassContext, GlassError, HeadlessGlass, RenderData,
+    };
+
+    #[derive(Default)]
+    struct ClearApp {
+        started: bool,
+    }
+
+    impl GlassApp for ClearApp {
+        fn start(&mut self, event_loop: Option<&EventLoop<()>>, _context: &mut GlassContext) {
+            assert!(event_loop.is_none());
+            self.started = true;
+        }
+
+        fn render(&mut self, _context: &GlassContext, render_data: RenderData) {
+            let RenderData {
+                encoder,
+                target,
+                frame,
+            } = render_data;
+            assert!(target.window().is_none());
+            let view = frame.create_view(&wgpu::TextureViewDescriptor::default());
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::RED),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+        }
+    }
+
+    #[test]
+    fn test_windowed_stages_render_offscreen() {
+        // Needs an adapter
+        let Ok(mut glass) = HeadlessGlass::new(ClearApp::default(), GlassConfig::performance(4, 4))
+        else {
+            return;
+        };
+        glass.step().unwrap();
+        assert!(glass.app().started);
+        let image = glass.read_target(0).unwrap();
+        assert_eq!(image.get_pixel(2, 2), &Rgba([255, 0, 0, 255]));
+
+        let texture = Texture::empty(
+            glass.context().device(),
+            "Float Target",
+            Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+            1,
+            TextureFormat::R32Float,
+            &SamplerDescriptor::default(),
+            TextureUsages::COPY_SRC,
+        );
+        assert!(matches!(
+            read_texture_rgba8(glass.context(), &texture.texture),
+            Err(GlassError::TextureReadError {
+                format: TextureFormat::R32Float
+            })
+        ));
+    }
+
+    #[test]
+    fn test_f16_to_f32() {
+        assert_eq!(f16_to_f32(0x0000), 0.0);
+        assert_eq!(f16_to_f32(0x3c00), 1.0);
+        assert_eq!(f16_to_f32(0xc000), -2.0);
+        assert_eq!(f16_to_f32(0x3800), 0.5);
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24));
+        assert_eq!(f16_to_f32(0x7c00), f32::INFINITY);
+        assert!(f16_to_f32(0x7e00).is_nan());
+    }
+
+    #[test]
+    fn test_linear_f16_to_srgb8() {
+        // Linear (0.5, 2.0, -1.0, 0.5)
+        let bytes = [0x3800u16, 0x4000, 0xbc00, 0x3800]
+            .iter()
+            .flat_map(|h| h.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(linear_f16_to_srgb8(&bytes), vec![188, 255, 0, 128]);
+    }
+}
@@ -0,0 +1,296 @@
+use std::collections::HashSet;
+
+use glam::Vec2;
+use winit::{
+    event::{
+        ElementState, ModifiersState, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent,
+    },
+    window::Window,
+};
+
+use crate::recording::RecordedWindowEvent;
+
+/// Input state of a window, updated from winit events by [`Glass`](crate::Glass). Pressed &
+/// released states and deltas are reset after
+/// [`GlassApp::end_of_frame`](crate::GlassApp::end_of_frame), so they describe what happened
+/// since the previous frame. Access it through
+/// [`GlassWindow::input`](crate::window::GlassWindow::input).
+#[derive(Debug, Clone)]
+pub struct InputState {
+    keys_held: HashSet<VirtualKeyCode>,
+    keys_pressed: HashSet<VirtualKeyCode>,
+    keys_released: HashSet<VirtualKeyCode>,
+    mouse_held: HashSet<MouseButton>,
+    mouse_pressed: HashSet<MouseButton>,
+    mouse_released: HashSet<MouseButton>,
+    cursor_position: Option<Vec2>,
+    cursor_delta: Vec2,
+    scroll_delta: Vec2,
+    modifiers: ModifiersState,
+    scale_factor: f64,
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl InputState {
+    pub fn new(scale_factor: f64) -> InputState {
+        InputState {
+            keys_held: HashSet::default(),
+            keys_pressed: HashSet::default(),
+            keys_released: HashSet::default(),
+            mouse_held: HashSet::default(),
+            mouse_pressed: HashSet::default(),
+            mouse_released: HashSet::default(),
+            cursor_position: None,
+            cursor_delta: Vec2::ZERO,
+            scroll_delta: Vec2::ZERO,
+            modifiers: ModifiersState::empty(),
+            scale_factor,
+        }
+    }
+
+    pub(crate) fn from_window(window: &Window) -> InputState {
+        Self::new(window.scale_factor())
+    }
+
+    /// Update state from a window event
+    pub fn process_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput {
+                input, ..
+            } => {
+                if let Some(key) = input.virtual_keycode {
+                    match input.state {
+                        ElementState::Pressed => self.press_key(key),
+                        ElementState::Released => self.release_key(key),
+                    }
+                }
+            }
+            WindowEvent::MouseInput {
+                state,
+                button,
+                ..
+            } => match state {
+                ElementState::Pressed => self.press_mouse(*button),
+                ElementState::Released => self.release_mouse(*button),
+            },
+            WindowEvent::CursorMoved {
+                position, ..
+            } => self.move_cursor(Vec2::new(position.x as f32, position.y as f32)),
+            WindowEvent::CursorLeft {
+                ..
+            } => self.cursor_position = None,
+            WindowEvent::MouseWheel {
+                delta, ..
+            } => match delta {
+                MouseScrollDelta::LineDelta(x, y) => self.scroll_delta += Vec2::new(*x, *y),
+                MouseScrollDelta::PixelDelta(position) => {
+                    self.scroll_delta += Vec2::new(position.x as f32, position.y as f32)
+                }
+            },
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = *modifiers,
+            WindowEvent::ScaleFactorChanged {
+                scale_factor, ..
+            } => self.scale_factor = *scale_factor,
+            // Release everything, we won't receive release events while unfocused
+            WindowEvent::Focused(false) => self.release_all(),
+            _ => (),
+        }
+    }
+
+    /// Update state from a replayed event
+    pub(crate) fn process_recorded_event(&mut self, event: &RecordedWindowEvent) {
+        match event {
+            RecordedWindowEvent::ScaleFactorChanged {
+                scale_factor,
+            } => self.scale_factor = *scale_factor,
+            event => {
+                if let Some(event) = event.to_window_event() {
+                    self.process_event(&event);
+                }
+            }
+        }
+    }
+
+    /// Reset per frame state. Held keys & buttons remain.
+    pub(crate) fn end_frame(&mut self) {
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.mouse_pressed.clear();
+        self.mouse_released.clear();
+        self.cursor_delta = Vec2::ZERO;
+        self.scroll_delta = Vec2::ZERO;
+    }
+
+    pub(crate) fn press_key(&mut self, key: VirtualKeyCode) {
+        // Ignore key repeat
+        if self.keys_held.insert(key) {
+            self.keys_pressed.insert(key);
+        }
+    }
+
+    pub(crate) fn release_key(&mut self, key: VirtualKeyCode) {
+        if self.keys_held.remove(&key) {
+            self.keys_released.insert(key);
+        }
+    }
+
+    pub(crate) fn press_mouse(&mut self, button: MouseButton) {
+        if self.mouse_held.insert(button) {
+            self.mouse_pressed.insert(button);
+        }
+    }
+
+    pub(crate) fn release_mouse(&mut self, button: MouseButton) {
+        if self.mouse_held.remove(&button) {
+            self.mouse_released.insert(button);
+        }
+    }
+
+    pub(crate) fn move_cursor(&mut self, position: Vec2) {
+        if let Some(prev) = self.cursor_position {
+            self.cursor_delta += position - prev;
+        }
+        self.cursor_position = Some(position);
+    }
+
+    fn release_all(&mut self) {
+        self.keys_released.extend(self.keys_held.drain());
+        self.mouse_released.extend(self.mouse_held.drain());
+    }
+
+    /// Whether key is held down
+    pub fn key_held(&self, key: VirtualKeyCode) -> bool {
+        self.keys_held.contains(&key)
+    }
+
+    /// Whether key was pressed this frame
+    pub fn key_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    /// Whether key was released this frame
+    pub fn key_released(&self, key: VirtualKeyCode) -> bool {
+        self.keys_released.contains(&key)
+    }
+
+    pub fn keys_held(&self) -> impl Iterator<Item = &VirtualKeyCode> {
+        self.keys_held.iter()
+    }
+
+    pub fn mouse_buttons_held(&self) -> impl Iterator<Item = &MouseButton> {
+        self.mouse_held.iter()
+    }
+
+    /// Keys pressed this frame, including those already released again
+    pub fn keys_pressed(&self) -> impl Iterator<Item = &VirtualKeyCode> {
+        self.keys_pressed.iter()
+    }
+
+    /// Mouse buttons pressed this frame, including those already released again
+    pub fn mouse_buttons_pressed(&self) -> impl Iterator<Item = &MouseButton> {
+        self.mouse_pressed.iter()
+    }
+
+    /// Whether mouse button is held down
+    pub fn mouse_held(&self, button: MouseButton) -> bool {
+        self.mouse_held.contains(&button)
+    }
+
+    /// Whether mouse button was pressed this frame
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_pressed.contains(&button)
+    }
+
+    /// Whether mouse button was released this frame
+    pub fn mouse_released(&self, button: MouseButton) -> bool {
+        self.mouse_released.contains(&button)
+    }
+
+    /// Cursor position in physical pixels, `None` when cursor is outside the window
+    pub fn cursor_position(&self) -> Option<Vec2> {
+        self.cursor_position
+    }
+
+    /// Cursor position in logical pixels, `None` when cursor is outside the window
+    pub fn cursor_logical_position(&self) -> Option<Vec2> {
+        self.cursor_position
+            .map(|position| position / self.scale_factor as f32)
+    }
+
+    /// Cursor movement this frame in physical pixels
+    pub fn cursor_delta(&self) -> Vec2 {
+        self.cursor_delta
+    }
+
+    /// Scroll this frame. In lines for mouse wheels, in pixels for touchpads.
+    pub fn scroll_delta(&self) -> Vec2 {
+        self.scroll_delta
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+    use winit::event::{MouseButton, VirtualKeyCode};
+
+    use crate::input::InputState;
+
+    #[test]
+    fn test_key_states() {
+        let mut input = InputState::default();
+        input.press_key(VirtualKeyCode::Space);
+        // Key repeat
+        input.press_key(VirtualKeyCode::Space);
+        assert!(input.key_pressed(VirtualKeyCode::Space));
+        assert!(input.key_held(VirtualKeyCode::Space));
+        input.end_frame();
+        assert!(!input.key_pressed(VirtualKeyCode::Space));
+        assert!(input.key_held(VirtualKeyCode::Space));
+        input.release_key(VirtualKeyCode::Space);
+        assert!(input.key_released(VirtualKeyCode::Space));
+        assert!(!input.key_held(VirtualKeyCode::Space));
+        input.end_frame();
+        assert!(!input.key_released(VirtualKeyCode::Space));
+    }
+
+    #[test]
+    fn test_release_all_on_focus_lost() {
+        let mut input = InputState::default();
+        input.press_key(VirtualKeyCode::W);
+        input.press_mouse(MouseButton::Left);
+        input.end_frame();
+        input.release_all();
+        assert!(!input.key_held(VirtualKeyCode::W));
+        assert!(input.key_released(VirtualKeyCode::W));
+        assert!(!input.mouse_held(MouseButton::Left));
+        assert!(input.mouse_released(MouseButton::Left));
+    }
+
+    #[test]
+    fn test_cursor_delta() {
+        let mut input = InputState::new(2.0);
+        input.move_cursor(Vec2::new(10.0, 10.0));
+        // No delta from first position
+        assert_eq!(input.cursor_delta(), Vec2::ZERO);
+        input.move_cursor(Vec2::new(15.0, 10.0));
+        input.move_cursor(Vec2::new(20.0, 14.0));
+        assert_eq!(input.cursor_delta(), Vec2::new(10.0, 4.0));
+        assert_eq!(input.cursor_logical_position(), Some(Vec2::new(10.0, 7.0)));
+        input.end_frame();
+        assert_eq!(input.cursor_delta(), Vec2::ZERO);
+        assert_eq!(input.cursor_position(), Some(Vec2::new(20.0, 14.0)));
+    }
+}
@@ -1,9 +1,18 @@
+pub mod actions;
+mod compute;
 pub mod device_context;
+mod error;
 mod glass;
 mod glass_app;
+mod glass_plugin;
+mod headless;
+pub mod input;
+mod runner;
 
 pub mod pipelines;
+pub mod recording;
 pub mod texture;
+pub mod time;
 pub mod utils;
 pub mod window;
 
@@ -23,4 +32,4 @@ pub use image;
 pub use wgpu;
 pub use winit;
 
-pub use crate::{glass::*, glass_app::*};
+pub use crate::{compute::*, error::*, glass::*, glass_app::*, glass_plugin::*, headless::*};
@@ -1,19 +1,19 @@
-use std::borrow::Cow;
-
 use bytemuck::{Pod, Zeroable};
 use glam::{UVec2, UVec4, Vec4};
 use wgpu::{
     util::DeviceExt, AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry,
     BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, BlendComponent,
     BlendFactor, BlendOperation, BlendState, Buffer, Color, ColorTargetState, ColorWrites,
-    CommandEncoder, Device, Extent3d, FilterMode, LoadOp, Operations, PushConstantRange,
-    RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, SamplerBindingType,
-    SamplerDescriptor, ShaderStages, TextureFormat, TextureSampleType, TextureUsages,
-    TextureViewDimension,
+    CommandEncoder, Device, Extent3d, FilterMode, LoadOp, Operations, RenderPassColorAttachment,
+    RenderPassDescriptor, RenderPipeline, SamplerBindingType, SamplerDescriptor, ShaderStages,
+    TextureFormat, TextureSampleType, TextureUsages, TextureViewDimension,
 };
 
 use crate::{
-    pipelines::{SimpleVertex, FULL_SCREEN_TRIANGLE_VERTICES},
+    pipelines::{
+        constants::{pipeline_layout, shader_source, PipelineConstants, PASS_UNIFORM_SLOTS},
+        supports_push_constants, SimpleVertex, FULL_SCREEN_TRIANGLE_VERTICES,
+    },
     texture::Texture,
 };
 
@@ -56,6 +56,7 @@ pub struct BloomPipeline {
     width: u32,
     height: u32,
     settings: BloomSettings,
+    constants: PipelineConstants<BloomPushConstants>,
 }
 
 impl BloomPipeline {
@@ -108,16 +109,18 @@ impl BloomPipeline {
 
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Bloom Shader"),
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("bloom.wgsl"))),
-        });
-        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Bloom Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
-            push_constant_ranges: &[PushConstantRange {
-                stages: ShaderStages::FRAGMENT,
-                range: 0..std::mem::size_of::<BloomPushConstants>() as u32,
-            }],
+            source: wgpu::ShaderSource::Wgsl(shader_source(
+                include_str!("bloom.wgsl"),
+                supports_push_constants(device),
+                1,
+            )),
         });
+        let layout = pipeline_layout::<BloomPushConstants>(
+            device,
+            "Bloom Pipeline Layout",
+            &[&bind_group_layout],
+            ShaderStages::FRAGMENT,
+        );
         let downsample_first_pipeline =
             device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
                 label: Some("Bloom Downsample First Pipeline"),
@@ -245,6 +248,15 @@ impl BloomPipeline {
             mip_count,
         );
 
+        // All passes share the layout & constants
+        let constants = PipelineConstants::new(
+            device,
+            &downsample_pipeline,
+            ShaderStages::FRAGMENT,
+            1,
+            PASS_UNIFORM_SLOTS,
+        );
+
         BloomPipeline {
             downsample_first_pipeline,
             downsample_pipeline,
@@ -258,6 +270,7 @@ impl BloomPipeline {
             width,
             height,
             settings: bloom_settings,
+            constants,
         }
     }
 
@@ -336,6 +349,7 @@ impl BloomPipeline {
             viewport_size,
             UVec2::new(size[0] as u32, size[1] as u32),
         );
+        let slot = self.constants.write(&push_constants);
         // First downsample pass (main image)
         let downsampling_first_bind_group = device.create_bind_group(&BindGroupDescriptor {
             label: Some("bloom_downsampling_first_bind_group"),
@@ -367,11 +381,8 @@ impl BloomPipeline {
             first_downsample_pass.set_pipeline(&self.downsample_first_pipeline);
             first_downsample_pass.set_bind_group(0, &downsampling_first_bind_group, &[]);
             first_downsample_pass.set_vertex_buffer(0, self.vertices.slice(..));
-            first_downsample_pass.set_push_constants(
-                ShaderStages::FRAGMENT,
-                0,
-                bytemuck::cast_slice(&[push_constants]),
-            );
+            self.constants
+                .set(&mut first_downsample_pass, slot, &push_constants);
             first_downsample_pass.draw(0..3, 0..1);
         }
 
@@ -400,11 +411,8 @@ impl BloomPipeline {
                 &[],
             );
             downsampling_pass.set_vertex_buffer(0, self.vertices.slice(..));
-            downsampling_pass.set_push_constants(
-                ShaderStages::FRAGMENT,
-                0,
-                bytemuck::cast_slice(&[push_constants]),
-            );
+            self.constants
+                .set(&mut downsampling_pass, slot, &push_constants);
             downsampling_pass.draw(0..3, 0..1);
         }
 
@@ -440,11 +448,8 @@ impl BloomPipeline {
                 b: blend as f64,
                 a: 1.0,
             });
-            upsampling_pass.set_push_constants(
-                ShaderStages::FRAGMENT,
-                0,
-                bytemuck::cast_slice(&[push_constants]),
-            );
+            self.constants
+                .set(&mut upsampling_pass, slot, &push_constants);
             upsampling_pass.draw(0..3, 0..1);
         }
 
@@ -484,11 +489,8 @@ impl BloomPipeline {
                 b: blend as f64,
                 a: 1.0,
             });
-            upsampling_final_pass.set_push_constants(
-                ShaderStages::FRAGMENT,
-                0,
-                bytemuck::cast_slice(&[push_constants]),
-            );
+            self.constants
+                .set(&mut upsampling_final_pass, slot, &push_constants);
             upsampling_final_pass.draw(0..3, 0..1);
         }
     }
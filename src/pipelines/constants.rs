@@ -0,0 +1,293 @@
+use std::{
+    borrow::Cow,
+    marker::PhantomData,
+    num::NonZeroU64,
+    sync::{Arc, Mutex, Weak},
+};
+
+use bytemuck::Pod;
+use wgpu::{
+    BindGroup, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource,
+    BindingType, Buffer, BufferBinding, BufferBindingType, BufferUsages, Device, Features, Id,
+    PipelineLayout, PushConstantRange, Queue, RenderPass, RenderPipeline, ShaderStages,
+};
+
+/// Uniform slots of built-in pipelines that write their constants once per pass
+pub(crate) const PASS_UNIFORM_SLOTS: u32 = 64;
+
+const PUSH_CONSTANT_DECLARATION: &str = "var<push_constant> pc: PushConstants;";
+
+/// Whether built-in pipelines pass their constants as push constants on `device`. Without
+/// [`Features::PUSH_CONSTANTS`], e.g. on the GL backend, they fall back to a uniform buffer bound
+/// with dynamic offsets.
+pub fn supports_push_constants(device: &Device) -> bool {
+    device.features().contains(Features::PUSH_CONSTANTS)
+}
+
+/// Shader source with `pc` declared as push constants, or as a uniform at `group` if push
+/// constants are not used.
+pub(crate) fn shader_source(
+    source: &'static str,
+    push_constants: bool,
+    group: u32,
+) -> Cow<'static, str> {
+    if push_constants {
+        Cow::Borrowed(source)
+    } else {
+        Cow::Owned(source.replace(
+            PUSH_CONSTANT_DECLARATION,
+            &format!(
+                "@group({}) @binding(0) var<uniform> pc: PushConstants;",
+                group
+            ),
+        ))
+    }
+}
+
+/// Pipeline layout with `bind_group_layouts`, and either a push constant range of `T` or a
+/// dynamic uniform bind group of `T` after them.
+pub(crate) fn pipeline_layout<T: Pod>(
+    device: &Device,
+    label: &str,
+    bind_group_layouts: &[&BindGroupLayout],
+    stages: ShaderStages,
+) -> PipelineLayout {
+    if supports_push_constants(device) {
+        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(label),
+            bind_group_layouts,
+            push_constant_ranges: &[PushConstantRange {
+                stages,
+                range: 0..std::mem::size_of::<T>() as u32,
+            }],
+        })
+    } else {
+        let uniform_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("constants_bind_group_layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: stages,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let mut layouts = bind_group_layouts.to_vec();
+        layouts.push(&uniform_layout);
+        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(label),
+            bind_group_layouts: &layouts,
+            push_constant_ranges: &[],
+        })
+    }
+}
+
+/// Constants of type `T` of a built-in pipeline. Set as push constants, or else written to a slot
+/// of a uniform buffer which is bound with a dynamic offset.
+pub(crate) struct PipelineConstants<T> {
+    stages: ShaderStages,
+    uniforms: Option<Arc<DynamicUniforms>>,
+    _marker: PhantomData<T>,
+}
+
+struct DynamicUniforms {
+    device: Id<Device>,
+    buffer: Buffer,
+    bind_group: BindGroup,
+    group: u32,
+    stride: u64,
+    capacity: u32,
+    // Values by slot, written since the last upload
+    pending: Mutex<Vec<Vec<u8>>>,
+}
+
+/// Uniform buffers of all pipelines without push constants, to upload their written values
+static DYNAMIC_UNIFORMS: Mutex<Vec<Weak<DynamicUniforms>>> = Mutex::new(Vec::new());
+
+/// Upload constants that built-in pipelines without push constants wrote on `device`, so they
+/// apply to the next submission on `queue`. [`GlassContext::submit`](crate::GlassContext::submit)
+/// does this, which also submits rendered frames. Call it yourself before submitting commands of
+/// built-in pipelines with [`Queue::submit`].
+pub fn upload_constants(device: &Device, queue: &Queue) {
+    let mut all_uniforms = DYNAMIC_UNIFORMS.lock().unwrap();
+    all_uniforms.retain(|uniforms| uniforms.strong_count() > 0);
+    for uniforms in all_uniforms.iter().filter_map(Weak::upgrade) {
+        if uniforms.device != device.global_id() {
+            continue;
+        }
+        for (slot, value) in uniforms.pending.lock().unwrap().drain(..).enumerate() {
+            queue.write_buffer(
+                &uniforms.buffer,
+                uniforms.offset(slot as u32) as u64,
+                &value,
+            );
+        }
+    }
+}
+
+impl<T: Pod> PipelineConstants<T> {
+    /// `pipeline` must have been created with a layout from [`pipeline_layout`], where `group` is
+    /// the number of its other bind groups. Uniform buffer has room for `capacity` values per
+    /// submission.
+    pub fn new(
+        device: &Device,
+        pipeline: &RenderPipeline,
+        stages: ShaderStages,
+        group: u32,
+        capacity: u32,
+    ) -> PipelineConstants<T> {
+        let uniforms = (!supports_push_constants(device)).then(|| {
+            let size = std::mem::size_of::<T>() as u64;
+            let alignment = device.limits().min_uniform_buffer_offset_alignment as u64;
+            // Wgsl rounds uniform struct sizes up to 16 bytes
+            let stride = size.next_multiple_of(16).next_multiple_of(alignment);
+            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Constants Uniform Buffer"),
+                size: stride * capacity as u64,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("constants_bind_group"),
+                layout: &pipeline.get_bind_group_layout(group),
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer: &buffer,
+                        offset: 0,
+                        size: NonZeroU64::new(stride),
+                    }),
+                }],
+            });
+            let uniforms = Arc::new(DynamicUniforms {
+                device: device.global_id(),
+                buffer,
+                bind_group,
+                group,
+                stride,
+                capacity,
+                pending: Mutex::new(vec![]),
+            });
+            DYNAMIC_UNIFORMS
+                .lock()
+                .unwrap()
+                .push(Arc::downgrade(&uniforms));
+            uniforms
+        });
+        PipelineConstants {
+            stages,
+            uniforms,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Write `value` to the next free slot, uploaded with [`upload_constants`]. Returns the slot
+    /// to [`PipelineConstants::set`], which is always 0 with push constants.
+    ///
+    /// # Panics
+    /// If all slots have been written since the last upload.
+    pub fn write(&self, value: &T) -> u32 {
+        let Some(uniforms) = &self.uniforms else {
+            return 0;
+        };
+        let mut pending = uniforms.pending.lock().unwrap();
+        let slot = pending.len() as u32;
+        if slot == uniforms.capacity {
+            // Release the lock so it's not poisoned
+            drop(pending);
+            panic!(
+                "Built-in pipeline constants are limited to {} values per submission without push \
+                 constants. Call glass::pipelines::upload_constants & submit in between.",
+                uniforms.capacity
+            );
+        }
+        pending.push(bytemuck::bytes_of(value).to_vec());
+        slot
+    }
+
+    /// Set `value` as push constants, or bind the uniform `slot` it was written to.
+    pub fn set<'r>(&'r self, rpass: &mut RenderPass<'r>, slot: u32, value: &T) {
+        match &self.uniforms {
+            Some(uniforms) => {
+                rpass.set_bind_group(uniforms.group, &uniforms.bind_group, &[
+                    uniforms.offset(slot)
+                ]);
+            }
+            None => rpass.set_push_constants(self.stages, 0, bytemuck::bytes_of(value)),
+        }
+    }
+}
+
+impl DynamicUniforms {
+    fn offset(&self, slot: u32) -> u32 {
+        (slot as u64 * self.stride) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::AssertUnwindSafe;
+
+    use naga::valid::{Capabilities, ValidationFlags, Validator};
+    use wgpu::{ColorTargetState, ColorWrites, ShaderStages, TextureFormat};
+
+    use crate::{
+        device_context::{DeviceConfig, DeviceContext},
+        pipelines::{
+            constants::{shader_source, PipelineConstants},
+            supports_push_constants, QuadPipeline,
+        },
+    };
+
+    #[test]
+    fn test_shaders_compile_in_both_variants() {
+        let shaders = [
+            include_str!("quad/quad.wgsl"),
+            include_str!("paste/paste.wgsl"),
+            include_str!("bloom/bloom.wgsl"),
+            include_str!("tonemapping/tonemapping.wgsl"),
+        ];
+        for source in shaders {
+            for push_constants in [true, false] {
+                let source = shader_source(source, push_constants, 1);
+                assert_eq!(source.contains("var<uniform>"), !push_constants);
+                let module = naga::front::wgsl::parse_str(&source).unwrap();
+                Validator::new(ValidationFlags::all(), Capabilities::all())
+                    .validate(&module)
+                    .unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_uniform_slots_are_freed_by_submit() {
+        // Needs an adapter without push constants, e.g. gl
+        let Ok(context) = DeviceContext::new_headless(&DeviceConfig::default()) else {
+            return;
+        };
+        if supports_push_constants(context.device()) {
+            return;
+        }
+        let pipeline = QuadPipeline::new_render_pipeline(context.device(), ColorTargetState {
+            format: TextureFormat::Rgba8Unorm,
+            blend: None,
+            write_mask: ColorWrites::ALL,
+        });
+        let constants = PipelineConstants::<[f32; 4]>::new(
+            context.device(),
+            &pipeline,
+            ShaderStages::VERTEX_FRAGMENT,
+            1,
+            2,
+        );
+        assert_eq!(constants.write(&[0.0; 4]), 0);
+        assert_eq!(constants.write(&[1.0; 4]), 1);
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| constants.write(&[2.0; 4])));
+        assert!(result.is_err());
+        context.submit(None).unwrap();
+        assert_eq!(constants.write(&[2.0; 4]), 0);
+    }
+}
@@ -1,10 +1,12 @@
 mod bloom;
+mod constants;
 mod paste;
 mod quad;
 mod tonemapping;
 mod vertex;
 
 pub use bloom::*;
+pub use constants::{supports_push_constants, upload_constants};
 pub use paste::*;
 pub use quad::*;
 pub use tonemapping::*;
@@ -1,17 +1,18 @@
-use std::borrow::Cow;
-
 use bytemuck::{Pod, Zeroable};
 use glam::Vec2;
 use wgpu::{
     util::DeviceExt, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
     BindGroupLayoutEntry, BindingResource, BindingType, Buffer, Color, ColorTargetState,
-    ColorWrites, CommandEncoder, Device, Operations, PushConstantRange, RenderPassColorAttachment,
+    ColorWrites, CommandEncoder, Device, Operations, RenderPassColorAttachment,
     RenderPassDescriptor, RenderPipeline, SamplerBindingType, ShaderStages, TextureFormat,
     TextureSampleType, TextureViewDimension,
 };
 
 use crate::{
-    pipelines::{TexturedVertex, QUAD_INDICES, TEXTURED_QUAD_VERTICES},
+    pipelines::{
+        constants::{pipeline_layout, shader_source, PipelineConstants, PASS_UNIFORM_SLOTS},
+        supports_push_constants, TexturedVertex, QUAD_INDICES, TEXTURED_QUAD_VERTICES,
+    },
     texture::Texture,
 };
 
@@ -19,6 +20,7 @@ pub struct PastePipeline {
     paste_pipeline: RenderPipeline,
     vertices: Buffer,
     indices: Buffer,
+    constants: PipelineConstants<PastePushConstants>,
 }
 
 impl PastePipeline {
@@ -72,16 +74,18 @@ impl PastePipeline {
         });
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Paste Shader"),
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("paste.wgsl"))),
-        });
-        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Paste Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
-            push_constant_ranges: &[PushConstantRange {
-                stages: ShaderStages::VERTEX_FRAGMENT,
-                range: 0..std::mem::size_of::<PastePushConstants>() as u32,
-            }],
+            source: wgpu::ShaderSource::Wgsl(shader_source(
+                include_str!("paste.wgsl"),
+                supports_push_constants(device),
+                1,
+            )),
         });
+        let layout = pipeline_layout::<PastePushConstants>(
+            device,
+            "Paste Pipeline Layout",
+            &[&bind_group_layout],
+            ShaderStages::VERTEX_FRAGMENT,
+        );
         let paste_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Paste Pipeline"),
             layout: Some(&layout),
@@ -108,10 +112,19 @@ impl PastePipeline {
             multiview: None,
         });
 
+        let constants = PipelineConstants::new(
+            device,
+            &paste_pipeline,
+            ShaderStages::VERTEX_FRAGMENT,
+            1,
+            PASS_UNIFORM_SLOTS,
+        );
+
         PastePipeline {
             paste_pipeline,
             vertices,
             indices,
+            constants,
         }
     }
 
@@ -155,6 +168,7 @@ impl PastePipeline {
                 },
             ],
         });
+        let slot = self.constants.write(&push_constants);
         {
             let mut r_pass = encoder.begin_render_pass(&RenderPassDescriptor {
                 label: Some("paste_pass"),
@@ -169,11 +183,7 @@ impl PastePipeline {
             r_pass.set_bind_group(0, &bind_group, &[]);
             r_pass.set_vertex_buffer(0, self.vertices.slice(..));
             r_pass.set_index_buffer(self.indices.slice(..), wgpu::IndexFormat::Uint16);
-            r_pass.set_push_constants(
-                ShaderStages::VERTEX_FRAGMENT,
-                0,
-                bytemuck::cast_slice(&[push_constants]),
-            );
+            self.constants.set(&mut r_pass, slot, &push_constants);
             r_pass.draw_indexed(0..(QUAD_INDICES.len() as u32), 0, 0..1);
         }
     }
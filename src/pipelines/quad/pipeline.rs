@@ -1,17 +1,28 @@
-use std::borrow::Cow;
-
 use bytemuck::{Pod, Zeroable};
 use wgpu::{
-    util::DeviceExt, BindGroup, Buffer, Device, PushConstantRange, RenderPass, RenderPipeline,
-    Sampler, ShaderStages, TextureView,
+    util::DeviceExt, BindGroup, Buffer, Device, RenderPass, RenderPipeline, Sampler, ShaderStages,
+    TextureView,
+};
+
+use crate::pipelines::{
+    constants::{pipeline_layout, shader_source, PipelineConstants},
+    supports_push_constants,
+    vertex::TexturedVertex,
+    QUAD_INDICES, TEXTURED_QUAD_VERTICES,
 };
 
-use crate::pipelines::{vertex::TexturedVertex, QUAD_INDICES, TEXTURED_QUAD_VERTICES};
+/// Quads drawn per submission without push constants, see [`QuadPipeline::draw`]
+pub const QUAD_UNIFORM_SLOTS: u32 = 1024;
 
+/// Draws textured quads. Per quad constants are passed as push constants if the device supports
+/// them. Otherwise they are written to one of [`QUAD_UNIFORM_SLOTS`] uniform buffer slots, which
+/// are uploaded before the frame is submitted, see
+/// [`upload_constants`](crate::pipelines::upload_constants).
 pub struct QuadPipeline {
     pipeline: RenderPipeline,
     vertices: Buffer,
     indices: Buffer,
+    constants: PipelineConstants<QuadPushConstants>,
 }
 
 impl QuadPipeline {
@@ -27,10 +38,18 @@ impl QuadPipeline {
             usage: wgpu::BufferUsages::INDEX,
         });
         let pipeline = Self::new_render_pipeline(device, color_target_state);
+        let constants = PipelineConstants::new(
+            device,
+            &pipeline,
+            ShaderStages::VERTEX_FRAGMENT,
+            1,
+            QUAD_UNIFORM_SLOTS,
+        );
         Self {
             pipeline,
             vertices,
             indices,
+            constants,
         }
     }
 
@@ -64,16 +83,18 @@ impl QuadPipeline {
             });
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("quad.wgsl"))),
-        });
-        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Quad Pipeline Layout"),
-            bind_group_layouts: &[&texture_bind_group_layout],
-            push_constant_ranges: &[PushConstantRange {
-                stages: ShaderStages::VERTEX_FRAGMENT,
-                range: 0..std::mem::size_of::<QuadPushConstants>() as u32,
-            }],
+            source: wgpu::ShaderSource::Wgsl(shader_source(
+                include_str!("quad.wgsl"),
+                supports_push_constants(device),
+                1,
+            )),
         });
+        let layout = pipeline_layout::<QuadPushConstants>(
+            device,
+            "Quad Pipeline Layout",
+            &[&texture_bind_group_layout],
+            ShaderStages::VERTEX_FRAGMENT,
+        );
         let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Quad Render Pipeline"),
             layout: Some(&layout),
@@ -146,6 +167,10 @@ impl QuadPipeline {
         bind_group
     }
 
+    /// Draw a quad.
+    ///
+    /// # Panics
+    /// Without push constants, if more than [`QUAD_UNIFORM_SLOTS`] quads are drawn per submission.
     pub fn draw<'r>(
         &'r self,
         rpass: &mut RenderPass<'r>,
@@ -159,16 +184,9 @@ impl QuadPipeline {
         rpass.set_bind_group(0, bind_group, &[]);
         rpass.set_vertex_buffer(0, self.vertices.slice(..));
         rpass.set_index_buffer(self.indices.slice(..), wgpu::IndexFormat::Uint16);
-        rpass.set_push_constants(
-            ShaderStages::VERTEX_FRAGMENT,
-            0,
-            bytemuck::cast_slice(&[QuadPipeline::push_constants(
-                view_pos,
-                view_proj,
-                quad_size,
-                aa_strength,
-            )]),
-        );
+        let constants = QuadPipeline::push_constants(view_pos, view_proj, quad_size, aa_strength);
+        let slot = self.constants.write(&constants);
+        self.constants.set(rpass, slot, &constants);
         rpass.draw_indexed(0..(QUAD_INDICES.len() as u32), 0, 0..1);
     }
 }
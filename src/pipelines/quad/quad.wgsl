@@ -35,17 +35,15 @@ var input_texture: texture_2d<f32>;
 @group(0)@binding(1)
 var s: sampler;
 
-// https://www.shadertoy.com/view/MllBWf
-fn get_coords_aa(uv: vec2<f32>) -> vec2<f32> {
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(input_texture));
+    // Anti-aliased coords, https://www.shadertoy.com/view/MllBWf
+    // Inlined, because the glsl backend would emit a helper using fwidth in the vertex shader too
+    let uv = in.tex_coords * size;
     let fl = floor(uv + 0.5);
     var fr = fract(uv + 0.5);
     let aa = fwidth(uv) * pc.aa_strength * 0.5;
     fr = smoothstep(0.5 - aa, 0.5 + aa, fr);
-    return fl + fr - 0.5;
-}
-
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let size = vec2<f32>(textureDimensions(input_texture));
-    return in.color * textureSample(input_texture, s, get_coords_aa(in.tex_coords * size) / size);
+    return in.color * textureSample(input_texture, s, (fl + fr - 0.5) / size);
 }
\ No newline at end of file
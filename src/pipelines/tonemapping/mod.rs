@@ -1,3 +1,3 @@
 mod pipeline;
 
-pub use pipeline::{ColorGrading, TonemappingPipeline};
+pub use pipeline::{ColorGrading, TonemappingOutput, TonemappingPipeline};
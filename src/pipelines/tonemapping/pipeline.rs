@@ -1,16 +1,17 @@
-use std::borrow::Cow;
-
 use bytemuck::{Pod, Zeroable};
 use wgpu::{
     util::DeviceExt, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
     BindGroupLayoutEntry, BindingResource, BindingType, Buffer, ColorTargetState, ColorWrites,
-    CommandEncoder, Device, Operations, PushConstantRange, RenderPassColorAttachment,
-    RenderPassDescriptor, RenderPipeline, SamplerBindingType, ShaderStages, TextureFormat,
-    TextureSampleType, TextureViewDimension,
+    CommandEncoder, Device, Operations, RenderPassColorAttachment, RenderPassDescriptor,
+    RenderPipeline, SamplerBindingType, ShaderStages, TextureFormat, TextureSampleType,
+    TextureViewDimension,
 };
 
 use crate::{
-    pipelines::{SimpleVertex, FULL_SCREEN_TRIANGLE_VERTICES},
+    pipelines::{
+        constants::{pipeline_layout, shader_source, PipelineConstants, PASS_UNIFORM_SLOTS},
+        supports_push_constants, SimpleVertex, FULL_SCREEN_TRIANGLE_VERTICES,
+    },
     texture::Texture,
 };
 
@@ -19,6 +20,8 @@ const TONEMAPPING_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
 pub struct TonemappingPipeline {
     tonemapping_pipeline: RenderPipeline,
     vertices: Buffer,
+    constants: PipelineConstants<ToneMappingPushConstants>,
+    output: TonemappingOutput,
 }
 
 impl TonemappingPipeline {
@@ -54,16 +57,18 @@ impl TonemappingPipeline {
         });
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Tonemapping Shader"),
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("tonemapping.wgsl"))),
-        });
-        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Tonemapping Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
-            push_constant_ranges: &[PushConstantRange {
-                stages: ShaderStages::FRAGMENT,
-                range: 0..std::mem::size_of::<ToneMappingPushConstants>() as u32,
-            }],
+            source: wgpu::ShaderSource::Wgsl(shader_source(
+                include_str!("tonemapping.wgsl"),
+                supports_push_constants(device),
+                1,
+            )),
         });
+        let layout = pipeline_layout::<ToneMappingPushConstants>(
+            device,
+            "Tonemapping Pipeline Layout",
+            &[&bind_group_layout],
+            ShaderStages::FRAGMENT,
+        );
         let tonemapping_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Tonemapping Pipeline"),
             layout: Some(&layout),
@@ -87,12 +92,31 @@ impl TonemappingPipeline {
             multiview: None,
         });
 
+        let constants = PipelineConstants::new(
+            device,
+            &tonemapping_pipeline,
+            ShaderStages::FRAGMENT,
+            1,
+            PASS_UNIFORM_SLOTS,
+        );
+
         TonemappingPipeline {
             tonemapping_pipeline,
             vertices,
+            constants,
+            output: TonemappingOutput::default(),
         }
     }
 
+    /// Set how colors are mapped for the display, see [`TonemappingOutput`]
+    pub fn set_output(&mut self, output: TonemappingOutput) {
+        self.output = output;
+    }
+
+    pub fn output(&self) -> TonemappingOutput {
+        self.output
+    }
+
     pub fn tonemap(
         &self,
         device: &Device,
@@ -101,7 +125,16 @@ impl TonemappingPipeline {
         output: &Texture,
         color_grading: ColorGrading,
     ) {
-        let push_constants: ToneMappingPushConstants = color_grading.into();
+        let mut push_constants: ToneMappingPushConstants = color_grading.into();
+        if let TonemappingOutput::Hdr {
+            paper_white,
+            max_luminance,
+        } = self.output
+        {
+            push_constants.hdr = 1;
+            push_constants.paper_white = paper_white;
+            push_constants.max_luminance = max_luminance;
+        }
         let bind_group = device.create_bind_group(&BindGroupDescriptor {
             label: Some("tonemapping_bind_group"),
             layout: &self.tonemapping_pipeline.get_bind_group_layout(0),
@@ -116,6 +149,7 @@ impl TonemappingPipeline {
                 },
             ],
         });
+        let slot = self.constants.write(&push_constants);
         {
             let mut r_pass = encoder.begin_render_pass(&RenderPassDescriptor {
                 label: Some("tonemapping_pass"),
@@ -129,11 +163,7 @@ impl TonemappingPipeline {
             r_pass.set_pipeline(&self.tonemapping_pipeline);
             r_pass.set_bind_group(0, &bind_group, &[]);
             r_pass.set_vertex_buffer(0, self.vertices.slice(..));
-            r_pass.set_push_constants(
-                ShaderStages::FRAGMENT,
-                0,
-                bytemuck::cast_slice(&[push_constants]),
-            );
+            self.constants.set(&mut r_pass, slot, &push_constants);
             r_pass.draw(0..3, 0..1);
         }
     }
@@ -147,6 +177,9 @@ pub struct ToneMappingPushConstants {
     pub gamma: f32,
     pub pre_saturation: f32,
     pub post_saturation: f32,
+    pub hdr: u32,
+    pub paper_white: f32,
+    pub max_luminance: f32,
 }
 
 impl From<ColorGrading> for ToneMappingPushConstants {
@@ -157,10 +190,40 @@ impl From<ColorGrading> for ToneMappingPushConstants {
             gamma: val.gamma,
             pre_saturation: val.pre_saturation,
             post_saturation: val.post_saturation,
+            hdr: 0,
+            paper_white: 0.0,
+            max_luminance: 0.0,
         }
     }
 }
 
+/// How [`TonemappingPipeline`] maps colors for the display. The output texture is
+/// [`TextureFormat::Rgba16Float`] in both cases, which can be an HDR surface directly, see
+/// [`HDR_SURFACE_FORMATS`](crate::window::HDR_SURFACE_FORMATS).
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum TonemappingOutput {
+    /// Tonemap into 0-1 for sdr surfaces, with dithering against banding
+    #[default]
+    Sdr,
+    /// Output extended linear srgb (scRGB) for hdr surfaces, where 1.0 is 80 nits. Scene white is
+    /// shown at `paper_white` nits and highlights roll off towards `max_luminance` nits. Scaling
+    /// by `paper_white / 80` is only correct where the platform presents an
+    /// [`TextureFormat::Rgba16Float`] surface as scRGB, see
+    /// [`HDR_SURFACE_FORMATS`](crate::window::HDR_SURFACE_FORMATS).
+    Hdr {
+        paper_white: f32,
+        max_luminance: f32,
+    },
+}
+
+impl TonemappingOutput {
+    /// Hdr output for a typical display of 1000 nits peak brightness
+    pub const HDR: Self = Self::Hdr {
+        paper_white: 200.0,
+        max_luminance: 1000.0,
+    };
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct ColorGrading {
     pub off: bool,
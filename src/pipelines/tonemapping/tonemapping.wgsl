@@ -24,6 +24,9 @@ struct PushConstants {
     gamma: f32,
     pre_saturation: f32,
     post_saturation: f32,
+    hdr: u32,
+    paper_white: f32,
+    max_luminance: f32,
 }
 var<push_constant> pc: PushConstants;
 
@@ -60,6 +63,13 @@ fn tonemapping_reinhard_luminance(color: vec3<f32>) -> vec3<f32> {
     return tonemapping_change_luminance(color, l_new);
 }
 
+// Reinhard rolling off towards peak instead of 1.0, keeps values well below peak nearly as is
+fn tonemapping_reinhard_luminance_peak(color: vec3<f32>, peak: f32) -> vec3<f32> {
+    let l_old = tonemapping_luminance(color);
+    let l_new = l_old / (1.0 + l_old / peak);
+    return tonemapping_change_luminance(color, l_new);
+}
+
 // Source: Advanced VR Rendering, GDC 2015, Alex Vlachos, Valve, Slide 49
 // https://media.steampowered.com/apps/valve/2015/Alex_Vlachos_Advanced_VR_Rendering_GDC2015.pdf
 fn screen_space_dither(frag_coord: vec2<f32>) -> vec3<f32> {
@@ -76,7 +86,12 @@ fn tone_mapping(in: vec4<f32>) -> vec4<f32> {
     color = color * powsafe(vec3(2.0), pc.exposure);
     color = max(color, vec3(0.0));
     // tone_mapping
-    color = tonemapping_reinhard_luminance(color.rgb);
+    if (pc.hdr == u32(1)) {
+        // Scene white at paper white, highlights up to the display's peak luminance
+        color = tonemapping_reinhard_luminance_peak(color.rgb, pc.max_luminance / pc.paper_white);
+    } else {
+        color = tonemapping_reinhard_luminance(color.rgb);
+    }
     // Perceptual post tonemapping grading
     color = saturation(color, pc.post_saturation);
 
@@ -91,6 +106,10 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     }
 
     var output_rgb = tone_mapping(hdr_color).rgb;
+    if (pc.hdr == u32(1)) {
+        // Extended linear srgb (scRGB), where 1.0 is 80 nits. Float output needs no dither.
+        return vec4<f32>(output_rgb * pc.paper_white / 80.0, hdr_color.a);
+    }
 
     // Deband dither
     output_rgb = powsafe(output_rgb.rgb, 1.0 / 2.2);
@@ -0,0 +1,353 @@
+use std::{path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use winit::{
+    dpi::PhysicalPosition,
+    event::{
+        DeviceId, ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+        ScanCode, TouchPhase, VirtualKeyCode, WindowEvent,
+    },
+};
+
+use crate::{
+    utils::{load_ron, save_ron},
+    GlassError,
+};
+
+/// Input events of a window that are recorded. Window ids change between runs, so windows are
+/// identified by their index in creation order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecordedWindowEvent {
+    KeyboardInput {
+        scancode: ScanCode,
+        state: ElementState,
+        virtual_keycode: Option<VirtualKeyCode>,
+    },
+    MouseInput {
+        state: ElementState,
+        button: MouseButton,
+    },
+    CursorMoved {
+        position: PhysicalPosition<f64>,
+    },
+    CursorEntered,
+    CursorLeft,
+    MouseWheel {
+        delta: MouseScrollDelta,
+    },
+    ModifiersChanged(ModifiersState),
+    ReceivedCharacter(char),
+    /// Losing focus releases held keys & buttons
+    Focused(bool),
+    /// Replayed to the input state only, the window is not resized
+    ScaleFactorChanged {
+        scale_factor: f64,
+    },
+}
+
+impl RecordedWindowEvent {
+    /// Returns `None` for events that are not input, e.g. resize or close requests
+    pub fn from_window_event(event: &WindowEvent) -> Option<RecordedWindowEvent> {
+        let event = match event {
+            WindowEvent::KeyboardInput {
+                input,
+                is_synthetic: false,
+                ..
+            } => RecordedWindowEvent::KeyboardInput {
+                scancode: input.scancode,
+                state: input.state,
+                virtual_keycode: input.virtual_keycode,
+            },
+            WindowEvent::MouseInput {
+                state,
+                button,
+                ..
+            } => RecordedWindowEvent::MouseInput {
+                state: *state,
+                button: *button,
+            },
+            WindowEvent::CursorMoved {
+                position, ..
+            } => RecordedWindowEvent::CursorMoved {
+                position: *position,
+            },
+            WindowEvent::CursorEntered {
+                ..
+            } => RecordedWindowEvent::CursorEntered,
+            WindowEvent::CursorLeft {
+                ..
+            } => RecordedWindowEvent::CursorLeft,
+            WindowEvent::MouseWheel {
+                delta, ..
+            } => RecordedWindowEvent::MouseWheel {
+                delta: *delta,
+            },
+            WindowEvent::ModifiersChanged(modifiers) => {
+                RecordedWindowEvent::ModifiersChanged(*modifiers)
+            }
+            WindowEvent::ReceivedCharacter(c) => RecordedWindowEvent::ReceivedCharacter(*c),
+            WindowEvent::Focused(focused) => RecordedWindowEvent::Focused(*focused),
+            WindowEvent::ScaleFactorChanged {
+                scale_factor, ..
+            } => RecordedWindowEvent::ScaleFactorChanged {
+                scale_factor: *scale_factor,
+            },
+            _ => return None,
+        };
+        Some(event)
+    }
+
+    /// Recreate the winit event with a dummy device id. `None` for scale factor changes, which
+    /// winit sends with the window's new size to adjust.
+    #[allow(deprecated)]
+    pub fn to_window_event(&self) -> Option<WindowEvent<'static>> {
+        // Safety: Dummy device id is only compared against, never used to access a device
+        let device_id = unsafe { DeviceId::dummy() };
+        let event = match self {
+            RecordedWindowEvent::KeyboardInput {
+                scancode,
+                state,
+                virtual_keycode,
+            } => WindowEvent::KeyboardInput {
+                device_id,
+                input: KeyboardInput {
+                    scancode: *scancode,
+                    state: *state,
+                    virtual_keycode: *virtual_keycode,
+                    modifiers: ModifiersState::empty(),
+                },
+                is_synthetic: false,
+            },
+            RecordedWindowEvent::MouseInput {
+                state,
+                button,
+            } => WindowEvent::MouseInput {
+                device_id,
+                state: *state,
+                button: *button,
+                modifiers: ModifiersState::empty(),
+            },
+            RecordedWindowEvent::CursorMoved {
+                position,
+            } => WindowEvent::CursorMoved {
+                device_id,
+                position: *position,
+                modifiers: ModifiersState::empty(),
+            },
+            RecordedWindowEvent::CursorEntered => WindowEvent::CursorEntered {
+                device_id,
+            },
+            RecordedWindowEvent::CursorLeft => WindowEvent::CursorLeft {
+                device_id,
+            },
+            RecordedWindowEvent::MouseWheel {
+                delta,
+            } => WindowEvent::MouseWheel {
+                device_id,
+                delta: *delta,
+                phase: TouchPhase::Moved,
+                modifiers: ModifiersState::empty(),
+            },
+            RecordedWindowEvent::ModifiersChanged(modifiers) => {
+                WindowEvent::ModifiersChanged(*modifiers)
+            }
+            RecordedWindowEvent::ReceivedCharacter(c) => WindowEvent::ReceivedCharacter(*c),
+            RecordedWindowEvent::Focused(focused) => WindowEvent::Focused(*focused),
+            RecordedWindowEvent::ScaleFactorChanged {
+                ..
+            } => return None,
+        };
+        Some(event)
+    }
+
+    /// Whether live events like this are ignored while replaying. Scale factor changes still
+    /// resize the window.
+    pub(crate) fn is_replaced_by_replay(&self) -> bool {
+        !matches!(self, RecordedWindowEvent::ScaleFactorChanged { .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// Index of the window in creation order
+    pub window: usize,
+    pub event: RecordedWindowEvent,
+}
+
+/// Events received before a frame, and the frame's delta time
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    pub delta: Duration,
+    pub events: Vec<RecordedEvent>,
+}
+
+/// Input events & frame timings of a session. Record with
+/// [`GlassContext::start_recording`](crate::GlassContext::start_recording) and replay with
+/// [`GlassContext::start_replay`](crate::GlassContext::start_replay).
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    pub fn load(path: impl AsRef<Path>) -> Result<InputRecording, GlassError> {
+        load_ron(path)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), GlassError> {
+        save_ron(self, path)
+    }
+}
+
+/// Collects events into frames while recording
+#[derive(Debug, Default)]
+pub(crate) struct InputRecorder {
+    recording: InputRecording,
+    pending_events: Vec<RecordedEvent>,
+}
+
+impl InputRecorder {
+    pub fn record_event(&mut self, window: usize, event: &WindowEvent) {
+        if let Some(event) = RecordedWindowEvent::from_window_event(event) {
+            self.pending_events.push(RecordedEvent {
+                window,
+                event,
+            });
+        }
+    }
+
+    /// Record a frame with events received since previous frame
+    pub fn record_frame(&mut self, delta: Duration) {
+        self.recording.frames.push(RecordedFrame {
+            delta,
+            events: std::mem::take(&mut self.pending_events),
+        });
+    }
+
+    pub fn finish(self) -> InputRecording {
+        self.recording
+    }
+}
+
+/// Plays back recorded frames one by one
+#[derive(Debug)]
+pub(crate) struct InputReplay {
+    recording: InputRecording,
+    next_frame: usize,
+}
+
+impl InputReplay {
+    pub fn new(recording: InputRecording) -> InputReplay {
+        InputReplay {
+            recording,
+            next_frame: 0,
+        }
+    }
+
+    pub fn next_frame(&mut self) -> Option<&RecordedFrame> {
+        let frame = self.recording.frames.get(self.next_frame)?;
+        self.next_frame += 1;
+        Some(frame)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_frame >= self.recording.frames.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use winit::{
+        dpi::{PhysicalPosition, PhysicalSize},
+        event::{ElementState, MouseButton, VirtualKeyCode, WindowEvent},
+    };
+
+    use crate::{
+        input::InputState,
+        recording::{InputRecorder, InputRecording, InputReplay, RecordedWindowEvent},
+    };
+
+    #[test]
+    fn test_event_conversion() {
+        let events = [
+            RecordedWindowEvent::KeyboardInput {
+                scancode: 57,
+                state: ElementState::Pressed,
+                virtual_keycode: Some(VirtualKeyCode::Space),
+            },
+            RecordedWindowEvent::MouseInput {
+                state: ElementState::Released,
+                button: MouseButton::Left,
+            },
+            RecordedWindowEvent::CursorMoved {
+                position: PhysicalPosition::new(10.0, 20.0),
+            },
+            RecordedWindowEvent::CursorLeft,
+            RecordedWindowEvent::Focused(false),
+        ];
+        for event in events {
+            assert_eq!(
+                RecordedWindowEvent::from_window_event(&event.to_window_event().unwrap()),
+                Some(event)
+            );
+        }
+        let mut size = PhysicalSize::new(800, 600);
+        let scale_factor_changed = WindowEvent::ScaleFactorChanged {
+            scale_factor: 2.0,
+            new_inner_size: &mut size,
+        };
+        let recorded = RecordedWindowEvent::from_window_event(&scale_factor_changed).unwrap();
+        assert_eq!(recorded, RecordedWindowEvent::ScaleFactorChanged {
+            scale_factor: 2.0,
+        });
+        assert!(recorded.to_window_event().is_none());
+        assert!(!recorded.is_replaced_by_replay());
+    }
+
+    #[test]
+    fn test_replay_focus_and_scale_factor() {
+        let mut input = InputState::new(1.0);
+        input.process_recorded_event(&RecordedWindowEvent::KeyboardInput {
+            scancode: 57,
+            state: ElementState::Pressed,
+            virtual_keycode: Some(VirtualKeyCode::Space),
+        });
+        input.process_recorded_event(&RecordedWindowEvent::Focused(false));
+        assert!(!input.key_held(VirtualKeyCode::Space));
+        assert!(input.key_released(VirtualKeyCode::Space));
+        input.process_recorded_event(&RecordedWindowEvent::ScaleFactorChanged {
+            scale_factor: 2.0,
+        });
+        assert_eq!(input.scale_factor(), 2.0);
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut recorder = InputRecorder::default();
+        recorder.record_frame(Duration::from_millis(16));
+        let event = RecordedWindowEvent::CursorMoved {
+            position: PhysicalPosition::new(1.0, 2.0),
+        };
+        recorder.record_event(1, &event.to_window_event().unwrap());
+        recorder.record_frame(Duration::from_millis(17));
+        let recording = recorder.finish();
+        assert_eq!(recording.frames.len(), 2);
+        assert!(recording.frames[0].events.is_empty());
+        assert_eq!(recording.frames[1].events[0].window, 1);
+
+        let source = ron::to_string(&recording).unwrap();
+        let loaded: InputRecording = ron::from_str(&source).unwrap();
+        assert_eq!(loaded, recording);
+
+        let mut replay = InputReplay::new(loaded);
+        assert_eq!(
+            replay.next_frame().unwrap().delta,
+            Duration::from_millis(16)
+        );
+        assert_eq!(replay.next_frame().unwrap().events[0].event, event);
+        assert!(replay.is_finished());
+        assert!(replay.next_frame().is_none());
+    }
+}
@@ -0,0 +1,328 @@
+use std::time::{Duration, Instant};
+
+use wgpu::{CommandEncoder, Texture};
+use winit::{
+    dpi::PhysicalSize,
+    event::Event,
+    event_loop::{EventLoop, EventLoopWindowTarget},
+    window::WindowId,
+};
+
+use crate::{
+    GlassApp, GlassContext, GlassError, GlassPlugin, GlassStage, RenderData, RenderTarget,
+};
+
+/// Runs the stages of the app & its plugins in the order described in [`GlassPlugin`], and
+/// records stage timings. Errors from the app's fallible stages are returned to the caller. wgpu
+/// validation errors are captured in an error scope around each stage, so they're reported with
+/// the stage & window they happened in. Shared by [`Glass`](crate::Glass) and
+/// [`HeadlessGlass`](crate::HeadlessGlass).
+pub(crate) struct GlassRunner<A, E: 'static> {
+    app: A,
+    plugins: Vec<Box<dyn GlassPlugin<E>>>,
+}
+
+impl<A: GlassApp<E>, E: 'static> GlassRunner<A, E> {
+    pub fn new(app: A) -> GlassRunner<A, E> {
+        GlassRunner {
+            app,
+            plugins: vec![],
+        }
+    }
+
+    pub fn add_plugin(&mut self, plugin: Box<dyn GlassPlugin<E>>) {
+        self.plugins.push(plugin);
+    }
+
+    pub fn app(&self) -> &A {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut A {
+        &mut self.app
+    }
+
+    pub fn start(
+        &mut self,
+        event_loop: Option<&EventLoop<E>>,
+        context: &mut GlassContext,
+    ) -> Result<(), GlassError> {
+        let start = Instant::now();
+        context.push_error_scope();
+        for plugin in self.plugins.iter_mut() {
+            plugin.start(event_loop, context);
+        }
+        let result = self.app.try_start(event_loop, context);
+        context.pop_error_scope(GlassStage::Start, None);
+        context.add_stage_time(GlassStage::Start, start);
+        self.window_created(context);
+        result
+    }
+
+    pub fn input(
+        &mut self,
+        context: &mut GlassContext,
+        event_loop: &EventLoopWindowTarget<E>,
+        event: &Event<E>,
+    ) -> Result<(), GlassError> {
+        let start = Instant::now();
+        context.push_error_scope();
+        for plugin in self.plugins.iter_mut() {
+            plugin.input(context, event_loop, event);
+        }
+        let result = self.app.try_input(context, event_loop, event);
+        let window_id = match event {
+            Event::WindowEvent {
+                window_id, ..
+            } => Some(*window_id),
+            _ => None,
+        };
+        context.pop_error_scope(GlassStage::Input, window_id);
+        context.add_stage_time(GlassStage::Input, start);
+        self.window_created(context);
+        result
+    }
+
+    pub fn user_event(&mut self, context: &mut GlassContext, event: E) -> Result<(), GlassError> {
+        let start = Instant::now();
+        context.push_error_scope();
+        for plugin in self.plugins.iter_mut() {
+            plugin.user_event(context, &event);
+        }
+        let result = self.app.try_user_event(context, event);
+        context.pop_error_scope(GlassStage::UserEvent, None);
+        context.add_stage_time(GlassStage::UserEvent, start);
+        self.window_created(context);
+        result
+    }
+
+    pub fn resized(
+        &mut self,
+        context: &mut GlassContext,
+        window_id: WindowId,
+        new_size: PhysicalSize<u32>,
+        scale_factor: f64,
+    ) -> Result<(), GlassError> {
+        let start = Instant::now();
+        context.push_error_scope();
+        for plugin in self.plugins.iter_mut() {
+            plugin.resized(context, window_id, new_size, scale_factor);
+        }
+        let result = self
+            .app
+            .try_resized(context, window_id, new_size, scale_factor);
+        context.pop_error_scope(GlassStage::Resized, Some(window_id));
+        context.add_stage_time(GlassStage::Resized, start);
+        result
+    }
+
+    /// Run fixed updates for the frame time accumulated since last frame
+    pub fn fixed_update(&mut self, context: &mut GlassContext) -> Result<(), GlassError> {
+        let start = Instant::now();
+        context.push_error_scope();
+        let mut result = Ok(());
+        for _ in 0..context.accumulate_fixed_time(context.delta_time()) {
+            for plugin in self.plugins.iter_mut() {
+                plugin.fixed_update(context);
+            }
+            result = self.app.try_fixed_update(context);
+            if result.is_err() {
+                break;
+            }
+        }
+        context.pop_error_scope(GlassStage::FixedUpdate, None);
+        context.add_stage_time(GlassStage::FixedUpdate, start);
+        result
+    }
+
+    pub fn update(&mut self, context: &mut GlassContext) -> Result<(), GlassError> {
+        let start = Instant::now();
+        context.push_error_scope();
+        for plugin in self.plugins.iter_mut() {
+            plugin.update(context);
+        }
+        let result = self.app.try_update(context);
+        context.pop_error_scope(GlassStage::Update, None);
+        context.add_stage_time(GlassStage::Update, start);
+        self.window_created(context);
+        result
+    }
+
+    /// Run render, returns time spent
+    pub fn render(
+        &mut self,
+        context: &GlassContext,
+        encoder: &mut CommandEncoder,
+        target: RenderTarget,
+        frame: &Texture,
+    ) -> Result<Duration, GlassError> {
+        let start = Instant::now();
+        context.push_error_scope();
+        let result = self.app.try_render(context, RenderData {
+            encoder,
+            target,
+            frame,
+        });
+        if result.is_ok() {
+            for plugin in self.plugins.iter_mut() {
+                plugin.render(context, RenderData {
+                    encoder,
+                    target,
+                    frame,
+                });
+            }
+        }
+        context.pop_error_scope(GlassStage::Render, target.window_id());
+        result.map(|_| start.elapsed())
+    }
+
+    /// Run post processing, returns time spent
+    pub fn post_processing(
+        &mut self,
+        context: &GlassContext,
+        encoder: &mut CommandEncoder,
+        target: RenderTarget,
+        frame: &Texture,
+    ) -> Result<Duration, GlassError> {
+        let start = Instant::now();
+        context.push_error_scope();
+        let result = self.app.try_post_processing(context, RenderData {
+            encoder,
+            target,
+            frame,
+        });
+        if result.is_ok() {
+            for plugin in self.plugins.iter_mut() {
+                plugin.post_processing(context, RenderData {
+                    encoder,
+                    target,
+                    frame,
+                });
+            }
+        }
+        context.pop_error_scope(GlassStage::PostProcessing, target.window_id());
+        result.map(|_| start.elapsed())
+    }
+
+    /// Run after render, returns time spent
+    pub fn after_render(&mut self, context: &GlassContext) -> Result<Duration, GlassError> {
+        let start = Instant::now();
+        context.push_error_scope();
+        let result = self.app.try_after_render(context);
+        if result.is_ok() {
+            for plugin in self.plugins.iter_mut() {
+                plugin.after_render(context);
+            }
+        }
+        context.pop_error_scope(GlassStage::AfterRender, None);
+        result.map(|_| start.elapsed())
+    }
+
+    pub fn device_recreated(&mut self, context: &mut GlassContext) -> Result<(), GlassError> {
+        let start = Instant::now();
+        context.push_error_scope();
+        for plugin in self.plugins.iter_mut() {
+            plugin.device_recreated(context);
+        }
+        let result = self.app.try_device_recreated(context);
+        context.pop_error_scope(GlassStage::DeviceRecreated, None);
+        context.add_stage_time(GlassStage::DeviceRecreated, start);
+        result
+    }
+
+    /// Report windows created since last call
+    pub fn window_created(&mut self, context: &mut GlassContext) {
+        for window_id in context.take_created_windows() {
+            for plugin in self.plugins.iter_mut() {
+                plugin.on_window_created(context, window_id);
+            }
+            self.app.on_window_created(context, window_id);
+        }
+    }
+
+    /// Whether the app & all plugins allow closing the window. All of them are asked.
+    pub fn close_requested(&mut self, context: &mut GlassContext, window_id: WindowId) -> bool {
+        let mut close = self.app.on_close_requested(context, window_id);
+        for plugin in self.plugins.iter_mut() {
+            close &= plugin.on_close_requested(context, window_id);
+        }
+        close
+    }
+
+    pub fn window_closed(&mut self, context: &mut GlassContext, window_id: WindowId) {
+        self.app.on_window_closed(context, window_id);
+        for plugin in self.plugins.iter_mut().rev() {
+            plugin.on_window_closed(context, window_id);
+        }
+    }
+
+    pub fn surface_timeout(&mut self, context: &mut GlassContext, window_id: WindowId) {
+        self.app.surface_timeout(context, window_id);
+        for plugin in self.plugins.iter_mut() {
+            plugin.surface_timeout(context, window_id);
+        }
+    }
+
+    pub fn end_of_frame(&mut self, context: &mut GlassContext) -> Result<(), GlassError> {
+        let start = Instant::now();
+        context.push_error_scope();
+        let result = self.app.try_end_of_frame(context);
+        if result.is_ok() {
+            for plugin in self.plugins.iter_mut() {
+                plugin.end_of_frame(context);
+            }
+        }
+        context.pop_error_scope(GlassStage::EndOfFrame, None);
+        context.add_stage_time(GlassStage::EndOfFrame, start);
+        result
+    }
+
+    pub fn end(&mut self, context: &mut GlassContext) {
+        let start = Instant::now();
+        context.push_error_scope();
+        self.app.end(context);
+        for plugin in self.plugins.iter_mut().rev() {
+            plugin.end(context);
+        }
+        context.pop_error_scope(GlassStage::End, None);
+        context.add_stage_time(GlassStage::End, start);
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use winit::{
+        event_loop::{EventLoop, EventLoopBuilder},
+        platform::x11::EventLoopBuilderExtX11,
+    };
+
+    use crate::{runner::GlassRunner, window::WindowConfig, GlassApp, GlassConfig, GlassContext};
+
+    struct CreateWindowApp;
+
+    impl GlassApp for CreateWindowApp {
+        fn start(&mut self, event_loop: Option<&EventLoop<()>>, context: &mut GlassContext) {
+            context
+                .create_window(event_loop.unwrap(), WindowConfig {
+                    visible: false,
+                    ..WindowConfig::default()
+                })
+                .unwrap();
+        }
+    }
+
+    #[test]
+    #[ignore = "needs a display"]
+    fn test_create_first_window_in_stage() {
+        // Tests don't run on the main thread
+        let event_loop = EventLoopBuilder::new().with_any_thread(true).build();
+        let mut context = GlassContext::new(&event_loop, GlassConfig::windowless()).unwrap();
+        let mut runner = GlassRunner::new(CreateWindowApp);
+        // The device is recreated for the window's surface within start's error scope
+        runner.start(Some(&event_loop), &mut context).unwrap();
+        assert!(context
+            .render_window(context.primary_render_window().window().id())
+            .is_some());
+        assert!(context.take_gpu_errors().is_empty());
+    }
+}
@@ -65,7 +65,12 @@ impl Texture {
     ) -> Result<Self, GlassError> {
         let img = match image::load_from_memory(bytes) {
             Ok(im) => im,
-            Err(e) => return Err(GlassError::ImageError(e)),
+            Err(e) => {
+                return Err(GlassError::ImageError {
+                    name: label.to_owned(),
+                    source: e,
+                })
+            }
         };
         Ok(Self::from_image(
             device,
@@ -0,0 +1,224 @@
+use std::{collections::VecDeque, time::Duration};
+
+use crate::GlassStage;
+
+/// Maximum number of fixed updates run per frame. Prevents the simulation from falling further
+/// and further behind when a frame takes longer than the fixed updates within it.
+const MAX_FIXED_STEPS_PER_FRAME: u32 = 8;
+
+/// Accumulates frame time and tells how many fixed updates should be run each frame.
+#[derive(Debug, Copy, Clone)]
+pub struct FixedTimestep {
+    timestep: Duration,
+    accumulator: Duration,
+}
+
+impl FixedTimestep {
+    pub fn new(updates_per_second: f64) -> FixedTimestep {
+        assert!(
+            updates_per_second > 0.0,
+            "Fixed update rate must be positive"
+        );
+        FixedTimestep {
+            timestep: Duration::from_secs_f64(1.0 / updates_per_second),
+            accumulator: Duration::ZERO,
+        }
+    }
+
+    /// Add frame time to the accumulator and return the number of fixed steps to run. If more
+    /// steps would be needed than allowed per frame, the excess time is dropped.
+    pub fn accumulate(&mut self, delta: Duration) -> u32 {
+        self.accumulator += delta;
+        let mut steps = 0;
+        while self.accumulator >= self.timestep && steps < MAX_FIXED_STEPS_PER_FRAME {
+            self.accumulator -= self.timestep;
+            steps += 1;
+        }
+        if self.accumulator >= self.timestep {
+            self.accumulator = Duration::from_nanos(
+                (self.accumulator.as_nanos() % self.timestep.as_nanos()) as u64,
+            );
+        }
+        steps
+    }
+
+    /// Duration of a single fixed step.
+    pub fn timestep(&self) -> Duration {
+        self.timestep
+    }
+
+    /// How far we are between the previous and the next fixed step, in range `[0, 1)`. Use this
+    /// to interpolate between simulation states when rendering.
+    pub fn alpha(&self) -> f64 {
+        self.accumulator.as_secs_f64() / self.timestep.as_secs_f64()
+    }
+}
+
+/// Number of frames kept for frame time statistics.
+const FRAME_TIME_WINDOW: usize = 120;
+/// Weight of the latest frame in smoothed fps.
+const FPS_SMOOTHING: f64 = 0.1;
+
+/// Frame timing & statistics, updated by [`Glass`](crate::Glass) each frame. Access it through
+/// [`GlassContext::frame_timer`](crate::GlassContext::frame_timer).
+#[derive(Debug, Clone)]
+pub struct FrameTimer {
+    frame_index: u64,
+    delta: Duration,
+    smoothed_delta: f64,
+    frame_times: VecDeque<Duration>,
+    stage_times: [Duration; GlassStage::COUNT],
+    last_stage_times: [Duration; GlassStage::COUNT],
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        Self {
+            frame_index: 0,
+            delta: Duration::ZERO,
+            smoothed_delta: 0.0,
+            frame_times: VecDeque::with_capacity(FRAME_TIME_WINDOW),
+            stage_times: [Duration::ZERO; GlassStage::COUNT],
+            last_stage_times: [Duration::ZERO; GlassStage::COUNT],
+        }
+    }
+}
+
+impl FrameTimer {
+    /// Start a new frame that took `delta` since the previous one.
+    pub(crate) fn begin_frame(&mut self, delta: Duration) {
+        self.delta = delta;
+        self.smoothed_delta = if self.frame_times.is_empty() {
+            delta.as_secs_f64()
+        } else {
+            self.smoothed_delta * (1.0 - FPS_SMOOTHING) + delta.as_secs_f64() * FPS_SMOOTHING
+        };
+        if self.frame_times.len() == FRAME_TIME_WINDOW {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(delta);
+    }
+
+    /// Add cpu time spent in a stage during the current frame.
+    pub(crate) fn add_stage_time(&mut self, stage: GlassStage, time: Duration) {
+        self.stage_times[stage as usize] += time;
+    }
+
+    /// Finish current frame, publishing its stage times.
+    pub(crate) fn end_frame(&mut self) {
+        self.last_stage_times = self.stage_times;
+        self.stage_times = [Duration::ZERO; GlassStage::COUNT];
+        self.frame_index += 1;
+    }
+
+    /// Index of the current frame, starting from zero.
+    pub fn frame_index(&self) -> u64 {
+        self.frame_index
+    }
+
+    /// Time between the start of the previous frame and the current frame.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// Frames per second, smoothed over recent frames.
+    pub fn fps(&self) -> f64 {
+        if self.smoothed_delta > 0.0 {
+            1.0 / self.smoothed_delta
+        } else {
+            0.0
+        }
+    }
+
+    /// Shortest frame time within the rolling window.
+    pub fn min_frame_time(&self) -> Duration {
+        self.frame_times.iter().min().copied().unwrap_or_default()
+    }
+
+    /// Longest frame time within the rolling window.
+    pub fn max_frame_time(&self) -> Duration {
+        self.frame_times.iter().max().copied().unwrap_or_default()
+    }
+
+    /// Average frame time within the rolling window.
+    pub fn average_frame_time(&self) -> Duration {
+        if self.frame_times.is_empty() {
+            return Duration::ZERO;
+        }
+        self.frame_times.iter().sum::<Duration>() / self.frame_times.len() as u32
+    }
+
+    /// Frame time at `percentile` (`0.0..=1.0`) within the rolling window. E.g. `0.99` gives the
+    /// frame time that 99% of frames were faster than or equal to.
+    pub fn frame_time_percentile(&self, percentile: f64) -> Duration {
+        if self.frame_times.is_empty() {
+            return Duration::ZERO;
+        }
+        let mut sorted = self.frame_times.iter().copied().collect::<Vec<_>>();
+        sorted.sort();
+        let index = ((sorted.len() - 1) as f64 * percentile.clamp(0.0, 1.0)).round() as usize;
+        sorted[index]
+    }
+
+    /// Cpu time spent in a stage during the previous frame. Render stages are summed over all
+    /// windows.
+    pub fn stage_time(&self, stage: GlassStage) -> Duration {
+        self.last_stage_times[stage as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        time::{FixedTimestep, FrameTimer, MAX_FIXED_STEPS_PER_FRAME},
+        GlassStage,
+    };
+
+    #[test]
+    fn test_fixed_timestep_accumulation() {
+        let mut fixed = FixedTimestep::new(10.0);
+        assert_eq!(fixed.accumulate(Duration::from_millis(50)), 0);
+        assert!((fixed.alpha() - 0.5).abs() < 1e-6);
+        assert_eq!(fixed.accumulate(Duration::from_millis(75)), 1);
+        assert!((fixed.alpha() - 0.25).abs() < 1e-6);
+        assert_eq!(fixed.accumulate(Duration::from_millis(200)), 2);
+        assert!((fixed.alpha() - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fixed_timestep_max_steps() {
+        let mut fixed = FixedTimestep::new(100.0);
+        assert_eq!(
+            fixed.accumulate(Duration::from_millis(1005)),
+            MAX_FIXED_STEPS_PER_FRAME
+        );
+        // Excess time is dropped
+        assert!(fixed.alpha() < 1.0);
+        assert!((fixed.alpha() - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_frame_timer_statistics() {
+        let mut timer = FrameTimer::default();
+        for ms in 1..=10 {
+            timer.begin_frame(Duration::from_millis(ms));
+            timer.add_stage_time(GlassStage::Update, Duration::from_millis(1));
+            timer.add_stage_time(GlassStage::Update, Duration::from_millis(1));
+            timer.end_frame();
+        }
+        assert_eq!(timer.frame_index(), 10);
+        assert_eq!(timer.delta(), Duration::from_millis(10));
+        assert_eq!(timer.min_frame_time(), Duration::from_millis(1));
+        assert_eq!(timer.max_frame_time(), Duration::from_millis(10));
+        assert_eq!(timer.frame_time_percentile(0.5), Duration::from_millis(6));
+        assert_eq!(timer.frame_time_percentile(1.0), Duration::from_millis(10));
+        assert_eq!(
+            timer.stage_time(GlassStage::Update),
+            Duration::from_millis(2)
+        );
+        assert_eq!(timer.stage_time(GlassStage::Render), Duration::ZERO);
+        assert!(timer.fps() > 100.0 && timer.fps() < 1000.0);
+    }
+}
@@ -1,44 +1,47 @@
 use std::{
     borrow::Cow,
     collections::{HashMap, HashSet, VecDeque},
-    fmt::Formatter,
     future::Future,
     path::{Path, PathBuf},
 };
 
 use naga::Module;
 use path_clean::PathClean;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::GlassError;
 
 pub fn wait_async<F: Future>(fut: F) -> F::Output {
     pollster::block_on(fut)
 }
 
-#[derive(Debug, PartialEq)]
-pub enum ShaderError {
-    FileReadError(String),
-    InvalidExtension(String),
-    AlreadyIncluded(String),
-    WgslParseError(String),
+/// Load a value from a [ron](https://github.com/ron-rs/ron) file
+pub fn load_ron<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T, GlassError> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path).map_err(|e| GlassError::IoError {
+        path: path.to_owned(),
+        source: e,
+    })?;
+    ron::from_str(&source).map_err(|e| GlassError::RonDeserializeError {
+        path: path.to_owned(),
+        source: e,
+    })
 }
 
-impl std::fmt::Display for ShaderError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let s = match self {
-            ShaderError::FileReadError(e) => {
-                format!("ShaderError::FileReadError: {}", e)
-            }
-            ShaderError::InvalidExtension(e) => {
-                format!("ShaderError::InvalidExtension: {}", e)
-            }
-            ShaderError::AlreadyIncluded(e) => {
-                format!("ShaderError::AlreadyIncluded: {}", e)
+/// Save a value to a [ron](https://github.com/ron-rs/ron) file
+pub fn save_ron<T: Serialize>(value: &T, path: impl AsRef<Path>) -> Result<(), GlassError> {
+    let path = path.as_ref();
+    let source =
+        ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()).map_err(|e| {
+            GlassError::RonSerializeError {
+                path: path.to_owned(),
+                source: e,
             }
-            ShaderError::WgslParseError(e) => {
-                format!("ShaderError::WgslParseError: \n{}", e)
-            }
-        };
-        write!(f, "{}", s)
-    }
+        })?;
+    std::fs::write(path, source).map_err(|e| GlassError::IoError {
+        path: path.to_owned(),
+        source: e,
+    })
 }
 
 impl From<ShaderModule> for Module {
@@ -53,7 +56,7 @@ pub struct ShaderModule {
 }
 
 impl ShaderModule {
-    pub fn new(source_filepath: &str) -> Result<ShaderModule, ShaderError> {
+    pub fn new(source_filepath: &str) -> Result<ShaderModule, GlassError> {
         let source = ShaderSource::new(source_filepath)?;
         Self::new_from_source(source)
     }
@@ -61,12 +64,12 @@ impl ShaderModule {
     pub fn new_with_static_sources(
         root_source_path: &str,
         include_srcs: &HashMap<&'static str, &'static str>,
-    ) -> Result<ShaderModule, ShaderError> {
+    ) -> Result<ShaderModule, GlassError> {
         let source = ShaderSource::new_with_static_sources(root_source_path, include_srcs)?;
         Self::new_from_source(source)
     }
 
-    pub fn new_from_source(source: ShaderSource) -> Result<ShaderModule, ShaderError> {
+    pub fn new_from_source(source: ShaderSource) -> Result<ShaderModule, GlassError> {
         let mut wgsl_parser = naga::front::wgsl::Frontend::new();
         match wgsl_parser.parse(&source.source) {
             Ok(module) => Ok(ShaderModule {
@@ -109,7 +112,10 @@ impl ShaderModule {
                         .collect::<Vec<String>>()
                 ));
 
-                Err(ShaderError::WgslParseError(error_str))
+                Err(GlassError::ShaderParseError {
+                    path: PathBuf::from(&source.path),
+                    message: error_str,
+                })
             }
         }
     }
@@ -123,7 +129,7 @@ pub struct ShaderSource {
 }
 
 impl ShaderSource {
-    pub fn new(source_filepath: &str) -> Result<ShaderSource, ShaderError> {
+    pub fn new(source_filepath: &str) -> Result<ShaderSource, GlassError> {
         let mut included_files = HashSet::new();
         let mut file_stack = VecDeque::new();
         let mut included_parts = Vec::new();
@@ -149,7 +155,7 @@ impl ShaderSource {
     pub fn new_with_static_sources(
         root_source_path: &str,
         include_srcs: &HashMap<&'static str, &'static str>,
-    ) -> Result<ShaderSource, ShaderError> {
+    ) -> Result<ShaderSource, GlassError> {
         let mut included_files = HashSet::new();
         let mut file_stack = VecDeque::new();
         let mut included_parts = Vec::new();
@@ -191,7 +197,7 @@ fn wgsl_source_with_includes(
     included_parts: &mut Vec<IncludedPart>,
     main_file_line_count: &mut usize,
     depth: usize,
-) -> Result<String, ShaderError> {
+) -> Result<String, GlassError> {
     let mut result = String::new();
     let file_path_str = file_path.to_string_lossy().into_owned();
 
@@ -203,7 +209,11 @@ fn wgsl_source_with_includes(
     if let Some(ext) = ext {
         match ext.as_str() {
             "wgsl" => {}
-            e => return Err(ShaderError::InvalidExtension(e.to_string())),
+            _ => {
+                return Err(GlassError::ShaderExtensionError {
+                    path: file_path.to_owned(),
+                })
+            }
         }
     }
 
@@ -212,10 +222,10 @@ fn wgsl_source_with_includes(
     let source = match std::fs::read_to_string(file_path) {
         Ok(str) => str,
         Err(e) => {
-            return Err(ShaderError::FileReadError(format!(
-                "{}: {}",
-                file_path_str, e
-            )));
+            return Err(GlassError::IoError {
+                path: file_path.to_owned(),
+                source: e,
+            });
         }
     };
 
@@ -229,10 +239,10 @@ fn wgsl_source_with_includes(
 
             let included_file_path_str = included_file_path.to_string_lossy().into_owned();
             if included_files.contains(&included_file_path_str) {
-                return Err(ShaderError::AlreadyIncluded(format!(
-                    "trying to include {} in {}",
-                    included_file_name, file_path_str
-                )));
+                return Err(GlassError::ShaderIncludeError {
+                    path: file_path.to_owned(),
+                    include: included_file_name.to_owned(),
+                });
             }
             if !file_stack.contains(&included_file_path_str) {
                 let included_part = wgsl_source_with_includes(
@@ -282,7 +292,7 @@ fn wgsl_source_with_static_includes(
     included_parts: &mut Vec<IncludedPart>,
     main_file_line_count: &mut usize,
     depth: usize,
-) -> Result<String, ShaderError> {
+) -> Result<String, GlassError> {
     let mut result = String::new();
     let file_path_str = file_path.to_string_lossy();
 
@@ -294,7 +304,11 @@ fn wgsl_source_with_static_includes(
     if let Some(ext) = ext {
         match ext.as_str() {
             "wgsl" => {}
-            e => return Err(ShaderError::InvalidExtension(e.to_string())),
+            _ => {
+                return Err(GlassError::ShaderExtensionError {
+                    path: file_path.to_owned(),
+                })
+            }
         }
     }
 
@@ -303,10 +317,9 @@ fn wgsl_source_with_static_includes(
     let source = match include_srcs.get(file_path_str.as_ref()) {
         Some(str) => str,
         None => {
-            return Err(ShaderError::FileReadError(format!(
-                "{}: Not found in statically included sources",
-                file_path_str
-            )));
+            return Err(GlassError::ShaderNotFoundError {
+                path: file_path.to_owned(),
+            });
         }
     };
 
@@ -320,10 +333,10 @@ fn wgsl_source_with_static_includes(
 
             let included_file_path_str = included_file_path.to_string_lossy().into_owned();
             if included_files.contains(&included_file_path_str) {
-                return Err(ShaderError::AlreadyIncluded(format!(
-                    "trying to include {} in {}",
-                    included_file_name, file_path_str
-                )));
+                return Err(GlassError::ShaderIncludeError {
+                    path: file_path.to_owned(),
+                    include: included_file_name.to_owned(),
+                });
             }
             if !file_stack.contains(&included_file_path_str) {
                 let included_part = wgsl_source_with_includes(
@@ -363,7 +376,10 @@ fn wgsl_source_with_static_includes(
 
 #[cfg(test)]
 mod tests {
-    use crate::utils::{ShaderError, ShaderModule, ShaderSource};
+    use crate::{
+        utils::{ShaderModule, ShaderSource},
+        GlassError,
+    };
 
     #[test]
     fn test_sequentially() {
@@ -466,7 +482,7 @@ const TEST2: u32 = u32(2);
         let _ = std::fs::remove_file(includes_file2);
 
         assert!(result.is_err());
-        assert!(matches!(result, Err(ShaderError::FileReadError(_))));
+        assert!(matches!(result, Err(GlassError::IoError { .. })));
     }
 
     fn test_file_already_included() {
@@ -499,7 +515,7 @@ const TEST2: u32 = u32(2);
         let _ = std::fs::remove_file(includes_file3);
 
         assert!(result.is_err());
-        assert!(matches!(result, Err(ShaderError::AlreadyIncluded(_))));
+        assert!(matches!(result, Err(GlassError::ShaderIncludeError { .. })));
     }
 
     fn test_invalid_extension() {
@@ -516,7 +532,10 @@ const TEST2: u32 = u32(2);
         let _ = std::fs::remove_file(includes_file1);
 
         assert!(result.is_err());
-        assert!(matches!(result, Err(ShaderError::InvalidExtension(_))));
+        assert!(matches!(
+            result,
+            Err(GlassError::ShaderExtensionError { .. })
+        ));
     }
 
     fn test_shader_parse_error1() {
@@ -550,7 +569,7 @@ const TEST2: u32 = i32(1);
         let _ = std::fs::remove_file(includes_file3);
 
         assert!(result.is_err());
-        assert!(matches!(result, Err(ShaderError::WgslParseError(_))));
+        assert!(matches!(result, Err(GlassError::ShaderParseError { .. })));
     }
 
     fn test_shader_parse_error2() {
@@ -585,7 +604,7 @@ const TEST3: u32 = i32(1);
         let _ = std::fs::remove_file(includes_file3);
 
         assert!(result.is_err());
-        assert!(matches!(result, Err(ShaderError::WgslParseError(_))));
+        assert!(matches!(result, Err(GlassError::ShaderParseError { .. })));
     }
 
     fn test_shader_parse_error3() {
@@ -622,7 +641,7 @@ const TEST4: u32 = i32(1);
         let _ = std::fs::remove_file(includes_file3);
 
         assert!(result.is_err());
-        assert!(matches!(result, Err(ShaderError::WgslParseError(_))));
+        assert!(matches!(result, Err(GlassError::ShaderParseError { .. })));
     }
 
     fn test_shader_parse_error4() {
@@ -649,6 +668,6 @@ const REDEF: u32 = i32(1);
         let _ = std::fs::remove_file(includes_file2);
 
         assert!(result.is_err());
-        assert!(matches!(result, Err(ShaderError::WgslParseError(_))));
+        assert!(matches!(result, Err(GlassError::ShaderParseError { .. })));
     }
 }
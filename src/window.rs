@@ -1,55 +1,369 @@
+use std::{cmp::Ordering, collections::BTreeMap, fmt::Formatter, path::Path};
+
 use glam::IVec2;
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
 use wgpu::{
-    CompositeAlphaMode, CreateSurfaceError, Device, PresentMode, Surface, SurfaceConfiguration,
-    TextureFormat,
+    Adapter, CompositeAlphaMode, CreateSurfaceError, Device, PresentMode, Surface,
+    SurfaceCapabilities, SurfaceConfiguration, SurfaceTexture, TextureFormat,
 };
 use winit::{
     dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
-    monitor::MonitorHandle,
-    window::{Fullscreen, Window},
+    monitor::{MonitorHandle, VideoMode},
+    window::{Fullscreen, Icon, Window, WindowLevel},
+};
+
+use crate::{
+    device_context::DeviceContext,
+    input::InputState,
+    utils::{load_ron, save_ron},
+    GlassError,
 };
 
-use crate::device_context::DeviceContext;
+/// Surface formats preferred by default, 8 bit srgb as most platforms support
+pub const DEFAULT_SURFACE_FORMATS: &[TextureFormat] =
+    &[TextureFormat::Bgra8UnormSrgb, TextureFormat::Rgba8UnormSrgb];
 
-#[derive(Debug, Copy, Clone)]
+/// Surface formats requesting hdr output, falling back to 8 bit srgb. The surface color space
+/// can't be chosen with wgpu, so output of e.g.
+/// [`TonemappingOutput::Hdr`](crate::pipelines::TonemappingOutput::Hdr) only has the intended
+/// brightness where the platform presents [`TextureFormat::Rgba16Float`] as extended linear srgb
+/// (scRGB), as with DirectX 12 on Windows. Elsewhere the float surface may be shown as sdr, or
+/// not be offered at all.
+pub const HDR_SURFACE_FORMATS: &[TextureFormat] = &[
+    TextureFormat::Rgba16Float,
+    TextureFormat::Bgra8UnormSrgb,
+    TextureFormat::Rgba8UnormSrgb,
+];
+
+#[derive(Debug, Clone)]
 pub struct WindowConfig {
-    pub title: &'static str,
+    pub title: String,
+    /// Load with [`load_icon`] or [`icon_from_image`]
+    pub icon: Option<Icon>,
     pub width: u32,
     pub height: u32,
     pub pos: WindowPos,
+    /// Surface formats in order of preference. The first one the surface supports is used, else
+    /// a supported srgb format. Check the result with [`GlassWindow::surface_format`]. Request hdr
+    /// output with [`HDR_SURFACE_FORMATS`].
+    pub surface_formats: Vec<TextureFormat>,
+    /// Used if the surface supports it, else [`PresentMode::Fifo`]
     pub present_mode: PresentMode,
+    /// Used if the surface supports it, else the first supported alpha mode
     pub alpha_mode: CompositeAlphaMode,
     pub max_size: Option<LogicalSize<u32>>,
     pub min_size: Option<LogicalSize<u32>>,
+    pub decorations: bool,
+    pub resizable: bool,
+    /// Transparent window background. With [`CompositeAlphaMode::Auto`], a premultiplied or
+    /// postmultiplied alpha mode is used if the surface supports one.
+    pub transparent: bool,
+    /// Whether the window is shown when created
+    pub visible: bool,
+    /// E.g. [`WindowLevel::AlwaysOnTop`] for tool palettes & overlays
+    pub window_level: WindowLevel,
+    /// Application id used by the desktop to group windows, e.g. the X11 `WM_CLASS` or the
+    /// Wayland app id. Ignored on platforms without one.
+    pub app_id: Option<String>,
+    /// Opt-in key of the window in
+    /// [`GlassConfig::window_geometry_file`](crate::GlassConfig::window_geometry_file). When set,
+    /// the window's geometry is saved when the app exits & restored instead of `pos`, `width` and
+    /// `height` when it is created next.
+    pub persistence_id: Option<String>,
     pub exit_on_esc: bool,
 }
 
 impl Default for WindowConfig {
     fn default() -> Self {
         Self {
-            title: "App",
+            title: "App".to_owned(),
+            icon: None,
             width: 1920,
             height: 1080,
-            pos: WindowPos::Centered,
+            pos: WindowPos::Centered(MonitorSelector::Primary),
+            surface_formats: DEFAULT_SURFACE_FORMATS.to_vec(),
             present_mode: PresentMode::AutoVsync,
             alpha_mode: CompositeAlphaMode::Auto,
             exit_on_esc: false,
             max_size: None,
             min_size: None,
+            decorations: true,
+            resizable: true,
+            transparent: false,
+            visible: true,
+            window_level: WindowLevel::Normal,
+            app_id: None,
+            persistence_id: None,
         }
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+/// Load a window icon from an image file
+pub fn load_icon(path: impl AsRef<Path>) -> Result<Icon, GlassError> {
+    let path = path.as_ref();
+    let image = image::open(path).map_err(|e| GlassError::ImageError {
+        name: path.display().to_string(),
+        source: e,
+    })?;
+    Ok(icon_from_image(&image))
+}
+
+pub fn icon_from_image(image: &DynamicImage) -> Icon {
+    let image = image.to_rgba8();
+    let (width, height) = image.dimensions();
+    // Size always matches the rgba data
+    Icon::from_rgba(image.into_raw(), width, height).unwrap()
+}
+
+/// Window placement. Monitor targeted variants fall back to the primary monitor if the selected
+/// one does not exist.
+#[derive(Debug, Clone, PartialEq)]
 pub enum WindowPos {
-    Centered,
-    FullScreen,
-    SizedFullScreen,
-    FullScreenBorderless,
-    Maximized,
+    Centered(MonitorSelector),
+    /// Exclusive fullscreen with the monitor's largest video mode
+    FullScreen(MonitorSelector),
+    /// Exclusive fullscreen with the video mode closest to the window size
+    SizedFullScreen(MonitorSelector),
+    FullScreenBorderless(MonitorSelector),
+    Maximized(MonitorSelector),
     Pos(PhysicalPosition<u32>),
 }
 
+impl WindowPos {
+    pub fn monitor(&self) -> Option<&MonitorSelector> {
+        match self {
+            WindowPos::Centered(monitor)
+            | WindowPos::FullScreen(monitor)
+            | WindowPos::SizedFullScreen(monitor)
+            | WindowPos::FullScreenBorderless(monitor)
+            | WindowPos::Maximized(monitor) => Some(monitor),
+            WindowPos::Pos(_) => None,
+        }
+    }
+}
+
+/// Selects a monitor for [`WindowPos`]. List the available ones with
+/// [`GlassContext::monitors`](crate::GlassContext::monitors). If the selected monitor is not
+/// connected, the primary monitor is used instead, or else the first one.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MonitorSelector {
+    #[default]
+    Primary,
+    /// Monitor the window is on. Same as primary when creating windows.
+    Current,
+    /// Index in [`GlassContext::monitors`](crate::GlassContext::monitors)
+    Index(usize),
+    /// Monitor with this name, see [`MonitorInfo::name`]
+    Name(String),
+}
+
+impl MonitorSelector {
+    /// Index of the selected monitor among monitors named `names`. Falls back to `primary`, or
+    /// else the first monitor, if the selected one does not exist. `None` without monitors.
+    pub(crate) fn select(
+        &self,
+        names: &[Option<String>],
+        primary: Option<usize>,
+        current: Option<usize>,
+    ) -> Option<usize> {
+        let selected = match self {
+            MonitorSelector::Primary => primary,
+            MonitorSelector::Current => current.or(primary),
+            MonitorSelector::Index(index) => (*index < names.len()).then_some(*index),
+            MonitorSelector::Name(name) => names
+                .iter()
+                .position(|n| n.as_deref() == Some(name.as_str())),
+        };
+        selected.or(primary).or((!names.is_empty()).then_some(0))
+    }
+}
+
+/// A connected monitor, see [`GlassContext::monitors`](crate::GlassContext::monitors)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    /// Index to select the monitor with [`MonitorSelector::Index`]
+    pub index: usize,
+    /// Human readable name, if the platform provides one
+    pub name: Option<String>,
+    /// Top left corner in the desktop
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+    pub scale_factor: f64,
+    pub is_primary: bool,
+    /// Exclusive fullscreen modes, largest first. Empty on platforms without them, e.g. Wayland.
+    pub video_modes: Vec<VideoModeInfo>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VideoModeInfo {
+    pub size: PhysicalSize<u32>,
+    pub bit_depth: u16,
+    pub refresh_rate_millihertz: u32,
+}
+
+impl MonitorInfo {
+    pub(crate) fn new(index: usize, monitor: &MonitorHandle, is_primary: bool) -> MonitorInfo {
+        let mut video_modes = monitor.video_modes().collect::<Vec<_>>();
+        video_modes.sort_by(compare_video_modes);
+        MonitorInfo {
+            index,
+            name: monitor.name(),
+            position: monitor.position(),
+            size: monitor.size(),
+            scale_factor: monitor.scale_factor(),
+            is_primary,
+            video_modes: video_modes
+                .iter()
+                .map(|mode| VideoModeInfo {
+                    size: mode.size(),
+                    bit_depth: mode.bit_depth(),
+                    refresh_rate_millihertz: mode.refresh_rate_millihertz(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Infos of `monitors`, in order
+pub(crate) fn monitor_infos(
+    monitors: &[MonitorHandle],
+    primary: Option<&MonitorHandle>,
+) -> Vec<MonitorInfo> {
+    monitors
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| MonitorInfo::new(index, monitor, primary == Some(monitor)))
+        .collect()
+}
+
+/// Monitor chosen by `selector` among `monitors`, see [`MonitorSelector::select`]
+pub(crate) fn select_monitor(
+    selector: &MonitorSelector,
+    monitors: &[MonitorHandle],
+    primary: Option<&MonitorHandle>,
+    current: Option<&MonitorHandle>,
+) -> Option<MonitorHandle> {
+    let names = monitors.iter().map(|m| m.name()).collect::<Vec<_>>();
+    let index_of = |monitor: Option<&MonitorHandle>| {
+        monitor.and_then(|monitor| monitors.iter().position(|m| m == monitor))
+    };
+    selector
+        .select(&names, index_of(primary), index_of(current))
+        .map(|index| monitors[index].clone())
+}
+
+/// Window position, size & state saved with [`WindowConfig::persistence_id`]. Position & size are
+/// those of the window when neither maximized nor fullscreen.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    /// Outer position in the desktop
+    pub position: PhysicalPosition<i32>,
+    /// Inner size
+    pub size: PhysicalSize<u32>,
+    pub maximized: bool,
+    /// Restored as borderless fullscreen
+    pub fullscreen: bool,
+    pub monitor: MonitorSelector,
+}
+
+impl WindowGeometry {
+    /// Geometry moved & shrunk to fit on its monitor, or on the primary monitor if that is no
+    /// longer connected, so it is never restored off-screen. Unchanged without monitors.
+    pub fn clamped(&self, monitors: &[MonitorInfo]) -> WindowGeometry {
+        let names = monitors.iter().map(|m| m.name.clone()).collect::<Vec<_>>();
+        let primary = monitors.iter().position(|m| m.is_primary);
+        let Some(index) = self.monitor.select(&names, primary, None) else {
+            return self.clone();
+        };
+        let monitor = &monitors[index];
+        let size = PhysicalSize::new(
+            self.size.width.min(monitor.size.width),
+            self.size.height.min(monitor.size.height),
+        );
+        let min = IVec2::new(monitor.position.x, monitor.position.y);
+        let max = min
+            + IVec2::new(
+                (monitor.size.width - size.width) as i32,
+                (monitor.size.height - size.height) as i32,
+            );
+        let position = IVec2::new(self.position.x, self.position.y).clamp(min, max);
+        WindowGeometry {
+            position: PhysicalPosition::new(position.x, position.y),
+            size,
+            maximized: self.maximized,
+            fullscreen: self.fullscreen,
+            monitor: self.monitor.clone(),
+        }
+    }
+}
+
+/// Saved [`WindowGeometry`] by [`WindowConfig::persistence_id`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometries {
+    pub windows: BTreeMap<String, WindowGeometry>,
+}
+
+impl WindowGeometries {
+    pub fn load(path: impl AsRef<Path>) -> Result<WindowGeometries, GlassError> {
+        load_ron(path)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), GlassError> {
+        save_ron(self, path)
+    }
+
+    pub fn get(&self, persistence_id: &str) -> Option<&WindowGeometry> {
+        self.windows.get(persistence_id)
+    }
+
+    pub fn insert(&mut self, persistence_id: String, geometry: WindowGeometry) {
+        self.windows.insert(persistence_id, geometry);
+    }
+}
+
+/// How to apply a [`WindowPos`] on its selected monitor
+pub(crate) enum Placement {
+    Fullscreen(Fullscreen),
+    Position(PhysicalPosition<i32>),
+    Maximized(Option<PhysicalPosition<i32>>),
+    /// No monitor to place the window on
+    Unchanged,
+}
+
+/// Placement of a window of `size` for `pos`. Exclusive fullscreen falls back to borderless
+/// fullscreen on monitors without video modes.
+pub(crate) fn window_placement(
+    pos: &WindowPos,
+    monitor: Option<&MonitorHandle>,
+    size: PhysicalSize<u32>,
+) -> Placement {
+    let exclusive = |mode: Option<VideoMode>| {
+        Placement::Fullscreen(match mode {
+            Some(mode) => Fullscreen::Exclusive(mode),
+            None => Fullscreen::Borderless(monitor.cloned()),
+        })
+    };
+    match (pos, monitor) {
+        (WindowPos::Pos(pos), _) => {
+            Placement::Position(PhysicalPosition::new(pos.x as i32, pos.y as i32))
+        }
+        // Borderless fullscreen & maximize also work without knowing the monitor
+        (WindowPos::FullScreenBorderless(_), _) => {
+            Placement::Fullscreen(Fullscreen::Borderless(monitor.cloned()))
+        }
+        (WindowPos::Maximized(_), _) => Placement::Maximized(monitor.map(|m| m.position())),
+        (_, None) => Placement::Unchanged,
+        (WindowPos::FullScreen(_), Some(monitor)) => exclusive(get_best_videomode(monitor)),
+        (WindowPos::SizedFullScreen(_), Some(monitor)) => {
+            exclusive(get_fitting_videomode(monitor, size.width, size.height))
+        }
+        (WindowPos::Centered(_), Some(monitor)) => Placement::Position(
+            get_centered_window_position(monitor, size.width, size.height),
+        ),
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum SurfaceError {
     /// A timeout was encountered while trying to acquire the next frame.
@@ -62,14 +376,56 @@ pub enum SurfaceError {
     OutOfMemory,
 }
 
+impl From<wgpu::SurfaceError> for SurfaceError {
+    fn from(value: wgpu::SurfaceError) -> Self {
+        match value {
+            wgpu::SurfaceError::Timeout => SurfaceError::Timeout,
+            wgpu::SurfaceError::Outdated => SurfaceError::Outdated,
+            wgpu::SurfaceError::Lost => SurfaceError::Lost,
+            wgpu::SurfaceError::OutOfMemory => SurfaceError::OutOfMemory,
+        }
+    }
+}
+
+impl std::fmt::Display for SurfaceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SurfaceError::Timeout => {
+                "A timeout was encountered while trying to acquire the next frame"
+            }
+            SurfaceError::Outdated => "The underlying surface has changed",
+            SurfaceError::Lost => "The swap chain has been lost",
+            SurfaceError::OutOfMemory => "There is no more memory left to allocate a new frame",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl std::error::Error for SurfaceError {}
+
 pub struct GlassWindow {
     window: Window,
     surface: Surface,
+    // Preferences of the window config, negotiated against the surface capabilities
+    preferred_formats: Vec<TextureFormat>,
+    preferred_present_mode: PresentMode,
+    preferred_alpha_mode: CompositeAlphaMode,
+    transparent: bool,
+    // Negotiated or configured, used when the surface is configured
+    surface_format: TextureFormat,
     present_mode: PresentMode,
     alpha_mode: CompositeAlphaMode,
     exit_on_esc: bool,
+    persistence_id: Option<String>,
+    // Geometry when neither maximized nor fullscreen
+    normal_position: PhysicalPosition<i32>,
+    normal_size: PhysicalSize<u32>,
     has_focus: bool,
+    needs_redraw: bool,
+    // Redraw requested from winit by Glass, whose RedrawRequested event is not a new request
+    redraw_requested: bool,
     last_surface_size: [u32; 2],
+    input: InputState,
 }
 
 impl GlassWindow {
@@ -80,28 +436,75 @@ impl GlassWindow {
         window: Window,
     ) -> Result<GlassWindow, CreateSurfaceError> {
         let size = [window.inner_size().width, window.inner_size().height];
-        let surface = unsafe {
-            match context.instance().create_surface(&window) {
-                Ok(surface) => surface,
-                Err(e) => return Err(e),
-            }
-        };
-        Ok(GlassWindow {
+        let surface = unsafe { context.instance().create_surface(&window)? };
+        let input = InputState::from_window(&window);
+        let normal_position = window.outer_position().unwrap_or_default();
+        let window_size = window.inner_size();
+        let surface_format = config
+            .surface_formats
+            .first()
+            .copied()
+            .unwrap_or(DEFAULT_SURFACE_FORMATS[0]);
+        let mut window = GlassWindow {
             window,
             surface,
+            preferred_formats: config.surface_formats,
+            preferred_present_mode: config.present_mode,
+            preferred_alpha_mode: config.alpha_mode,
+            transparent: config.transparent,
+            surface_format,
             present_mode: config.present_mode,
             alpha_mode: config.alpha_mode,
             exit_on_esc: config.exit_on_esc,
+            persistence_id: config.persistence_id,
+            normal_position,
+            normal_size: window_size,
             has_focus: false,
+            needs_redraw: true,
+            redraw_requested: false,
             last_surface_size: size,
-        })
+            input,
+        };
+        window.negotiate_surface(context.adapter());
+        Ok(window)
+    }
+
+    /// Choose format, present mode & alpha mode from the surface capabilities on `adapter` and
+    /// the preferences of the window config. Takes effect when the surface is configured next.
+    pub(crate) fn negotiate_surface(&mut self, adapter: &Adapter) {
+        let capabilities = self.surface.get_capabilities(adapter);
+        let (format, present_mode, alpha_mode) = negotiate_surface(
+            &capabilities,
+            &self.preferred_formats,
+            self.preferred_present_mode,
+            self.preferred_alpha_mode,
+            self.transparent,
+        );
+        self.surface_format = format;
+        self.present_mode = present_mode;
+        self.alpha_mode = alpha_mode;
+    }
+
+    /// Recreate the surface with the instance of a recreated device context and configure it with
+    /// the previous size
+    pub(crate) fn recreate_surface(
+        &mut self,
+        context: &DeviceContext,
+    ) -> Result<(), CreateSurfaceError> {
+        self.surface = unsafe { context.instance().create_surface(&self.window)? };
+        self.negotiate_surface(context.adapter());
+        let [width, height] = self.last_surface_size;
+        if width > 0 && height > 0 {
+            self.configure_surface_with_size(context.device(), PhysicalSize::new(width, height));
+        }
+        Ok(())
     }
 
     /// Configure surface after resize events
     pub(crate) fn configure_surface_with_size(&mut self, device: &Device, size: PhysicalSize<u32>) {
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: Self::surface_format(),
+            format: self.surface_format,
             width: size.width,
             height: size.height,
             present_mode: self.present_mode,
@@ -112,56 +515,64 @@ impl GlassWindow {
         self.last_surface_size = [size.width, size.height];
     }
 
+    /// Acquire next frame from the surface. If the surface is [`Outdated`](SurfaceError::Outdated)
+    /// or [`Lost`](SurfaceError::Lost), it is reconfigured and acquisition is retried once.
+    /// Minimized windows can't be reconfigured and return [`Outdated`](SurfaceError::Outdated).
+    pub(crate) fn acquire_frame(
+        &mut self,
+        device: &Device,
+    ) -> Result<SurfaceTexture, SurfaceError> {
+        match self.surface.get_current_texture() {
+            Ok(frame) => Ok(frame),
+            Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost) => {
+                if self.is_minimized() {
+                    return Err(SurfaceError::Outdated);
+                }
+                self.configure_surface_with_size(device, self.window.inner_size());
+                self.surface
+                    .get_current_texture()
+                    .map_err(SurfaceError::from)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
     /// Configure surface after window has changed. Use this to reconfigure the surface
     pub(crate) fn configure_surface(&mut self, device: &Device, config: &SurfaceConfiguration) {
         self.surface.configure(device, config);
+        // Preferences stay as configured, surfaces recreated after device loss negotiate from them
+        self.surface_format = config.format;
         self.present_mode = config.present_mode;
         self.alpha_mode = config.alpha_mode;
         self.last_surface_size = [config.width, config.height];
     }
 
+    /// Move the window to `window_position`. [`MonitorSelector::Current`] is the monitor the window
+    /// is on.
     pub fn set_position(&self, window_position: WindowPos) {
-        match window_position {
-            WindowPos::Maximized => {
-                self.window.set_fullscreen(None);
-                self.window.set_maximized(true)
-            }
-            WindowPos::FullScreen => {
-                if let Some(monitor) = self.window.current_monitor() {
-                    self.window
-                        .set_fullscreen(Some(Fullscreen::Exclusive(get_best_videomode(&monitor))));
-                }
-            }
-            WindowPos::SizedFullScreen => {
-                if let Some(monitor) = self.window.current_monitor() {
-                    let size = self.window.inner_size();
-                    self.window
-                        .set_fullscreen(Some(Fullscreen::Exclusive(get_fitting_videomode(
-                            &monitor,
-                            size.width,
-                            size.height,
-                        ))));
-                }
-            }
-            WindowPos::FullScreenBorderless => self
-                .window
-                .set_fullscreen(Some(Fullscreen::Borderless(self.window.current_monitor()))),
-            WindowPos::Pos(pos) => {
+        let monitor = window_position.monitor().and_then(|selector| {
+            select_monitor(
+                selector,
+                &self.window.available_monitors().collect::<Vec<_>>(),
+                self.window.primary_monitor().as_ref(),
+                self.window.current_monitor().as_ref(),
+            )
+        });
+        match window_placement(&window_position, monitor.as_ref(), self.window.inner_size()) {
+            Placement::Fullscreen(fullscreen) => self.window.set_fullscreen(Some(fullscreen)),
+            Placement::Position(pos) => {
                 self.window.set_fullscreen(None);
                 self.window.set_outer_position(pos)
             }
-            WindowPos::Centered => {
-                if let Some(monitor) = self.window.current_monitor() {
-                    self.window.set_fullscreen(None);
-                    let size = self.window.inner_size();
-                    self.window.set_outer_position(get_centered_window_position(
-                        &monitor,
-                        size.width,
-                        size.height,
-                    ));
+            Placement::Maximized(pos) => {
+                self.window.set_fullscreen(None);
+                if let Some(pos) = pos {
+                    self.window.set_outer_position(pos);
                 }
+                self.window.set_maximized(true)
             }
-        };
+            Placement::Unchanged => {}
+        }
     }
 
     /// Return [`Surface`](wgpu::Surface) belonging to the window
@@ -179,9 +590,58 @@ impl GlassWindow {
         self.present_mode
     }
 
-    /// Return [`TextureFormat`](wgpu::TextureFormat) belonging to the window surface
-    pub fn surface_format() -> TextureFormat {
-        TextureFormat::Bgra8UnormSrgb
+    /// Return [`TextureFormat`](wgpu::TextureFormat) of the window surface, chosen from the
+    /// surface capabilities & [`WindowConfig::surface_formats`]
+    pub fn surface_format(&self) -> TextureFormat {
+        self.surface_format
+    }
+
+    /// Whether the surface got an hdr format, e.g. when requested with [`HDR_SURFACE_FORMATS`]
+    pub fn is_hdr(&self) -> bool {
+        is_hdr_format(self.surface_format)
+    }
+
+    /// Return [`CompositeAlphaMode`](wgpu::CompositeAlphaMode) of the window surface
+    pub fn alpha_mode(&self) -> CompositeAlphaMode {
+        self.alpha_mode
+    }
+
+    pub fn persistence_id(&self) -> Option<&str> {
+        self.persistence_id.as_deref()
+    }
+
+    /// Remember position & `inner_size` to save them, unless maximized or fullscreen. Run after
+    /// the window was moved or resized.
+    pub(crate) fn update_normal_geometry(&mut self, inner_size: PhysicalSize<u32>) {
+        if self.window.is_maximized() || self.window.fullscreen().is_some() || self.is_minimized() {
+            return;
+        }
+        // Not supported e.g. on Wayland
+        if let Ok(position) = self.window.outer_position() {
+            self.normal_position = position;
+        }
+        self.normal_size = inner_size;
+    }
+
+    /// Current geometry to save & restore the window with
+    pub fn geometry(&self) -> WindowGeometry {
+        let monitors = self.window.available_monitors().collect::<Vec<_>>();
+        let monitor = self.window.current_monitor();
+        let monitor = match (monitor.as_ref().and_then(|m| m.name()), &monitor) {
+            (Some(name), _) => MonitorSelector::Name(name),
+            (None, Some(current)) => monitors
+                .iter()
+                .position(|m| m == current)
+                .map_or(MonitorSelector::Primary, MonitorSelector::Index),
+            (None, None) => MonitorSelector::Primary,
+        };
+        WindowGeometry {
+            position: self.normal_position,
+            size: self.normal_size,
+            maximized: self.window.is_maximized(),
+            fullscreen: self.window.fullscreen().is_some(),
+            monitor,
+        }
     }
 
     pub(crate) fn exit_on_esc(&self) -> bool {
@@ -196,69 +656,345 @@ impl GlassWindow {
         self.has_focus = has_focus;
     }
 
+    /// Whether the window will be rendered next frame with
+    /// [`ControlFlowPolicy::Reactive`](crate::ControlFlowPolicy::Reactive)
+    pub fn needs_redraw(&self) -> bool {
+        self.needs_redraw
+    }
+
+    pub(crate) fn set_needs_redraw(&mut self, needs_redraw: bool) {
+        self.needs_redraw = needs_redraw;
+    }
+
+    /// Ask winit for a redraw, e.g. to wake up the event loop
+    pub(crate) fn request_redraw(&mut self) {
+        self.redraw_requested = true;
+        self.window.request_redraw();
+    }
+
+    /// Handle winit's `RedrawRequested`. Redraws requested by the platform, e.g. after the window
+    /// was uncovered, mark the window to be rendered. Those requested by Glass don't, because the
+    /// window was marked already & may have been rendered since.
+    pub(crate) fn redraw_requested(&mut self) {
+        if !std::mem::take(&mut self.redraw_requested) {
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Input state of the window since the previous frame
+    pub fn input(&self) -> &InputState {
+        &self.input
+    }
+
+    pub(crate) fn input_mut(&mut self) -> &mut InputState {
+        &mut self.input
+    }
+
+    /// Whether the window has a zero size, e.g. when minimized on Windows
+    pub fn is_minimized(&self) -> bool {
+        let size = self.window.inner_size();
+        size.width == 0 || size.height == 0
+    }
+
     pub fn surface_size(&self) -> [u32; 2] {
         self.last_surface_size
     }
 }
 
+/// Whether surfaces of `format` can show values above 1.0
+pub fn is_hdr_format(format: TextureFormat) -> bool {
+    matches!(
+        format,
+        TextureFormat::Rgba16Float | TextureFormat::Rgba32Float
+    )
+}
+
+/// Choose surface format, present mode & alpha mode from `capabilities`, using the preferred ones
+/// where supported. Formats fall back to the first supported srgb format, present mode to
+/// [`PresentMode::Fifo`] and alpha mode to the first supported one. [`CompositeAlphaMode::Auto`]
+/// picks a blending alpha mode for `transparent` windows.
+pub fn negotiate_surface(
+    capabilities: &SurfaceCapabilities,
+    preferred_formats: &[TextureFormat],
+    preferred_present_mode: PresentMode,
+    preferred_alpha_mode: CompositeAlphaMode,
+    transparent: bool,
+) -> (TextureFormat, PresentMode, CompositeAlphaMode) {
+    let supported = &capabilities.formats;
+    let format = preferred_formats
+        .iter()
+        .find(|f| supported.contains(f))
+        .or_else(|| supported.iter().find(|f| f.is_srgb()))
+        .or(supported.first())
+        // Surface is not supported by the adapter, configuring it will fail anyway
+        .or(preferred_formats.first())
+        .copied()
+        .unwrap_or(DEFAULT_SURFACE_FORMATS[0]);
+    // Auto modes are resolved by wgpu & always supported
+    let present_mode = match preferred_present_mode {
+        PresentMode::AutoVsync | PresentMode::AutoNoVsync => preferred_present_mode,
+        mode if capabilities.present_modes.contains(&mode) => mode,
+        _ => PresentMode::Fifo,
+    };
+    let alpha_mode = match preferred_alpha_mode {
+        CompositeAlphaMode::Auto if transparent => [
+            CompositeAlphaMode::PreMultiplied,
+            CompositeAlphaMode::PostMultiplied,
+        ]
+        .into_iter()
+        .find(|mode| capabilities.alpha_modes.contains(mode))
+        .unwrap_or(CompositeAlphaMode::Auto),
+        CompositeAlphaMode::Auto => preferred_alpha_mode,
+        mode if capabilities.alpha_modes.contains(&mode) => mode,
+        _ => capabilities
+            .alpha_modes
+            .first()
+            .copied()
+            .unwrap_or(CompositeAlphaMode::Auto),
+    };
+    (format, present_mode, alpha_mode)
+}
+
+/// Position centering a window of the given size on `monitor`
 pub fn get_centered_window_position(
     monitor: &MonitorHandle,
     window_width: u32,
     window_height: u32,
 ) -> PhysicalPosition<i32> {
     let size = monitor.size();
-    let center = IVec2::new(size.width as i32, size.height as i32) / 2;
+    let origin = monitor.position();
+    let center =
+        IVec2::new(origin.x, origin.y) + IVec2::new(size.width as i32, size.height as i32) / 2;
     let window_size = PhysicalSize::new(window_width, window_height);
     let left_top = center - IVec2::new(window_size.width as i32, window_size.height as i32) / 2;
     PhysicalPosition::new(left_top.x, left_top.y)
 }
 
+/// Video mode of `monitor` closest to the given size, with the highest refresh rate. `None` if the
+/// monitor has no video modes.
 pub fn get_fitting_videomode(
-    monitor: &winit::monitor::MonitorHandle,
+    monitor: &MonitorHandle,
     width: u32,
     height: u32,
-) -> winit::monitor::VideoMode {
-    let mut modes = monitor.video_modes().collect::<Vec<_>>();
+) -> Option<VideoMode> {
+    monitor.video_modes().min_by(|a, b| {
+        a.size()
+            .width
+            .abs_diff(width)
+            .cmp(&b.size().width.abs_diff(width))
+            .then(
+                a.size()
+                    .height
+                    .abs_diff(height)
+                    .cmp(&b.size().height.abs_diff(height)),
+            )
+            .then(
+                b.refresh_rate_millihertz()
+                    .cmp(&a.refresh_rate_millihertz()),
+            )
+    })
+}
 
-    fn abs_diff(a: u32, b: u32) -> u32 {
-        if a > b {
-            return a - b;
-        }
-        b - a
-    }
-
-    modes.sort_by(|a, b| {
-        use std::cmp::Ordering::*;
-        match abs_diff(a.size().width, width).cmp(&abs_diff(b.size().width, width)) {
-            Equal => {
-                match abs_diff(a.size().height, height).cmp(&abs_diff(b.size().height, height)) {
-                    Equal => b
-                        .refresh_rate_millihertz()
-                        .cmp(&a.refresh_rate_millihertz()),
-                    default => default,
-                }
-            }
-            default => default,
-        }
-    });
+/// Largest video mode of `monitor`, with the highest refresh rate. `None` if the monitor has no
+/// video modes.
+pub fn get_best_videomode(monitor: &MonitorHandle) -> Option<VideoMode> {
+    monitor.video_modes().min_by(compare_video_modes)
+}
 
-    modes.first().unwrap().clone()
+/// Orders video modes largest & fastest first
+fn compare_video_modes(a: &VideoMode, b: &VideoMode) -> Ordering {
+    b.size()
+        .width
+        .cmp(&a.size().width)
+        .then(b.size().height.cmp(&a.size().height))
+        .then(
+            b.refresh_rate_millihertz()
+                .cmp(&a.refresh_rate_millihertz()),
+        )
 }
 
-pub fn get_best_videomode(monitor: &winit::monitor::MonitorHandle) -> winit::monitor::VideoMode {
-    let mut modes = monitor.video_modes().collect::<Vec<_>>();
-    modes.sort_by(|a, b| {
-        use std::cmp::Ordering::*;
-        match b.size().width.cmp(&a.size().width) {
-            Equal => match b.size().height.cmp(&a.size().height) {
-                Equal => b
-                    .refresh_rate_millihertz()
-                    .cmp(&a.refresh_rate_millihertz()),
-                default => default,
-            },
-            default => default,
-        }
-    });
+#[cfg(test)]
+mod tests {
+    use wgpu::{CompositeAlphaMode, PresentMode, SurfaceCapabilities, TextureFormat};
+    use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+    use crate::window::{
+        is_hdr_format, negotiate_surface, MonitorInfo, MonitorSelector, WindowGeometry,
+        DEFAULT_SURFACE_FORMATS, HDR_SURFACE_FORMATS,
+    };
+
+    #[test]
+    fn test_negotiate_surface() {
+        let capabilities = SurfaceCapabilities {
+            formats: vec![
+                TextureFormat::Rgba16Float,
+                TextureFormat::Rgba8Unorm,
+                TextureFormat::Rgba8UnormSrgb,
+            ],
+            present_modes: vec![PresentMode::Fifo, PresentMode::Mailbox],
+            alpha_modes: vec![CompositeAlphaMode::Opaque],
+        };
+        // Bgra is not supported, next preference is
+        assert_eq!(
+            negotiate_surface(
+                &capabilities,
+                DEFAULT_SURFACE_FORMATS,
+                PresentMode::Mailbox,
+                CompositeAlphaMode::Auto,
+                false
+            ),
+            (
+                TextureFormat::Rgba8UnormSrgb,
+                PresentMode::Mailbox,
+                CompositeAlphaMode::Auto
+            )
+        );
+        // Unsupported preferences fall back
+        assert_eq!(
+            negotiate_surface(
+                &capabilities,
+                &[TextureFormat::Bgra8Unorm],
+                PresentMode::Immediate,
+                CompositeAlphaMode::PreMultiplied,
+                false
+            ),
+            (
+                TextureFormat::Rgba8UnormSrgb,
+                PresentMode::Fifo,
+                CompositeAlphaMode::Opaque
+            )
+        );
+        assert_eq!(
+            negotiate_surface(
+                &capabilities,
+                &[TextureFormat::Rgba16Float],
+                PresentMode::AutoNoVsync,
+                CompositeAlphaMode::Opaque,
+                false
+            )
+            .0,
+            TextureFormat::Rgba16Float
+        );
+    }
+
+    #[test]
+    fn test_negotiate_hdr_surface() {
+        let sdr = SurfaceCapabilities {
+            formats: vec![TextureFormat::Bgra8UnormSrgb, TextureFormat::Bgra8Unorm],
+            present_modes: vec![PresentMode::Fifo],
+            alpha_modes: vec![CompositeAlphaMode::Opaque],
+        };
+        let hdr = SurfaceCapabilities {
+            formats: vec![TextureFormat::Bgra8UnormSrgb, TextureFormat::Rgba16Float],
+            present_modes: vec![PresentMode::Fifo],
+            alpha_modes: vec![CompositeAlphaMode::Opaque],
+        };
+        let negotiate = |capabilities| {
+            negotiate_surface(
+                capabilities,
+                HDR_SURFACE_FORMATS,
+                PresentMode::AutoVsync,
+                CompositeAlphaMode::Auto,
+                false,
+            )
+            .0
+        };
+        assert!(is_hdr_format(negotiate(&hdr)));
+        // Falls back to sdr
+        assert_eq!(negotiate(&sdr), TextureFormat::Bgra8UnormSrgb);
+    }
 
-    modes.first().unwrap().clone()
+    #[test]
+    fn test_negotiate_transparent_alpha_mode() {
+        let capabilities = SurfaceCapabilities {
+            formats: vec![TextureFormat::Bgra8UnormSrgb],
+            present_modes: vec![PresentMode::Fifo],
+            alpha_modes: vec![
+                CompositeAlphaMode::Opaque,
+                CompositeAlphaMode::PostMultiplied,
+            ],
+        };
+        let alpha_mode = |alpha_mode, transparent| {
+            negotiate_surface(
+                &capabilities,
+                DEFAULT_SURFACE_FORMATS,
+                PresentMode::AutoVsync,
+                alpha_mode,
+                transparent,
+            )
+            .2
+        };
+        assert_eq!(
+            alpha_mode(CompositeAlphaMode::Auto, true),
+            CompositeAlphaMode::PostMultiplied
+        );
+        assert_eq!(
+            alpha_mode(CompositeAlphaMode::Auto, false),
+            CompositeAlphaMode::Auto
+        );
+        // Explicit alpha mode wins
+        assert_eq!(
+            alpha_mode(CompositeAlphaMode::Opaque, true),
+            CompositeAlphaMode::Opaque
+        );
+    }
+
+    #[test]
+    fn test_select_monitor() {
+        let names = vec![Some("DP-1".to_owned()), None, Some("HDMI-1".to_owned())];
+        let select = |selector: MonitorSelector, current| selector.select(&names, Some(2), current);
+        assert_eq!(select(MonitorSelector::Primary, Some(0)), Some(2));
+        assert_eq!(select(MonitorSelector::Current, Some(1)), Some(1));
+        assert_eq!(select(MonitorSelector::Current, None), Some(2));
+        assert_eq!(select(MonitorSelector::Index(0), None), Some(0));
+        assert_eq!(
+            select(MonitorSelector::Name("DP-1".to_owned()), None),
+            Some(0)
+        );
+        // Missing monitors fall back to the primary one
+        assert_eq!(select(MonitorSelector::Index(3), None), Some(2));
+        assert_eq!(
+            select(MonitorSelector::Name("DP-2".to_owned()), None),
+            Some(2)
+        );
+        // Without a primary monitor, the first one
+        assert_eq!(
+            MonitorSelector::Index(3).select(&names, None, None),
+            Some(0)
+        );
+        assert_eq!(MonitorSelector::Primary.select(&[], None, None), None);
+    }
+
+    #[test]
+    fn test_clamp_window_geometry() {
+        let monitor = |index, name: &str, x, is_primary| MonitorInfo {
+            index,
+            name: Some(name.to_owned()),
+            position: PhysicalPosition::new(x, 0),
+            size: PhysicalSize::new(1920, 1080),
+            scale_factor: 1.0,
+            is_primary,
+            video_modes: vec![],
+        };
+        let monitors = [monitor(0, "DP-1", 0, true), monitor(1, "DP-2", 1920, false)];
+        let geometry = |x, y, width, height, name: &str| WindowGeometry {
+            position: PhysicalPosition::new(x, y),
+            size: PhysicalSize::new(width, height),
+            maximized: false,
+            fullscreen: false,
+            monitor: MonitorSelector::Name(name.to_owned()),
+        };
+        // Fits on its monitor
+        let on_screen = geometry(2000, 100, 800, 600, "DP-2");
+        assert_eq!(on_screen.clamped(&monitors), on_screen);
+        // Partly off-screen moves back onto its monitor
+        let clamped = geometry(3500, -50, 800, 600, "DP-2").clamped(&monitors);
+        assert_eq!(clamped.position, PhysicalPosition::new(3040, 0));
+        // Monitor was disconnected, moves to the primary one & shrinks to fit
+        let clamped = geometry(5000, 100, 2560, 600, "HDMI-1").clamped(&monitors);
+        assert_eq!(clamped.position, PhysicalPosition::new(0, 100));
+        assert_eq!(clamped.size, PhysicalSize::new(1920, 600));
+        // Without monitors, nothing to clamp to
+        assert_eq!(clamped.clamped(&[]), clamped);
+    }
 }